@@ -0,0 +1,176 @@
+// camera.rs
+// Efectos de cámara sobre el movimiento del jugador: balanceo al caminar,
+// suavizado del mouse, giro al ir de lado y hundimiento al aterrizar. Salvo el
+// suavizado, que filtra el giro, solo cambian la vista que se dibuja.
+use raylib::prelude::Vector2;
+use std::f32::consts::{PI, TAU};
+
+use crate::player::{Motion, Player};
+use crate::settings::CameraSettings;
+
+/// Largo de un paso, en bloques: el balanceo hace medio ciclo por paso.
+const STRIDE_FACTOR: f32 = 0.9;
+/// Altura del balanceo, en bloques, con intensidad 1.
+const BOB_FACTOR: f32 = 0.04;
+/// Giro de la vista al ir de lado, en radianes, con intensidad 1.
+const SWAY_ANGLE: f32 = 0.03;
+/// Cuánto baja el ojo al aterrizar, en bloques, y cuánto dura.
+const DIP_FACTOR: f32 = 0.12;
+const DIP_SECS: f32 = 0.25;
+/// Constante de tiempo del suavizado del mouse con intensidad 1.
+const SMOOTHING_SECS: f32 = 0.06;
+
+/// Pie que acaba de pisar, para alternar el sonido de los pasos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Foot { Left, Right }
+
+pub struct Camera {
+    settings: CameraSettings,
+    mouse: Vector2,
+    /// Fase del balanceo en radianes; el pie toca el suelo en cada múltiplo de π.
+    phase: f32,
+    /// Peso del balanceo: sube al caminar y baja al parar.
+    walking: f32,
+    sway: f32,
+    /// Segundos desde el último aterrizaje mientras dura el hundimiento.
+    since_landing: Option<f32>,
+    airborne: bool,
+    next_foot: Foot,
+}
+
+/// Factor para acercarse a un objetivo con constante de tiempo `secs`.
+#[inline]
+fn approach(dt: f32, secs: f32) -> f32 {
+    1.0 - (-dt / secs).exp()
+}
+
+impl Camera {
+    pub fn new(settings: CameraSettings) -> Camera {
+        Camera {
+            settings,
+            mouse: Vector2::zero(),
+            phase: 0.0,
+            walking: 0.0,
+            sway: 0.0,
+            since_landing: None,
+            airborne: false,
+            next_foot: Foot::Left,
+        }
+    }
+
+    /// Deja la cámara quieta, por ejemplo al empezar un nivel.
+    pub fn reset(&mut self) {
+        *self = Camera::new(self.settings);
+    }
+
+    /// Cambia los efectos, por ejemplo desde el menú de pausa.
+    pub fn set_settings(&mut self, settings: CameraSettings) {
+        self.settings = settings;
+    }
+
+    /// Movimiento del mouse de este frame, filtrado según `mouse_smoothing`.
+    pub fn smooth_mouse(&mut self, raw: Vector2, dt: f32) -> Vector2 {
+        let k = self.settings.mouse_smoothing.amount();
+        if k <= 0.0 || dt <= 0.0 {
+            self.mouse = raw;
+        } else {
+            let t = approach(dt, SMOOTHING_SECS * k);
+            self.mouse.x += (raw.x - self.mouse.x) * t;
+            self.mouse.y += (raw.y - self.mouse.y) * t;
+        }
+        self.mouse
+    }
+
+    /// Avanza los efectos con el movimiento del frame. Devuelve el pie que
+    /// pisa si en este frame se completó un paso.
+    pub fn update(&mut self, player: &Player, motion: Motion, block_size: usize, dt: f32) -> Option<Foot> {
+        let on_ground = player.z <= 0.0;
+        if self.airborne && on_ground { self.since_landing = Some(0.0); }
+        self.airborne = !on_ground;
+        if let Some(t) = &mut self.since_landing {
+            *t += dt;
+            if *t >= DIP_SECS { self.since_landing = None; }
+        }
+
+        let sway = motion.strafe * SWAY_ANGLE * self.settings.strafe_sway.amount();
+        self.sway += (sway - self.sway) * approach(dt, 0.1);
+
+        let moving = on_ground && motion.distance > 0.0;
+        self.walking += (if moving { 1.0 } else { 0.0 } - self.walking) * approach(dt, 0.12);
+        if !moving { return None; }
+
+        let before = (self.phase / PI).floor();
+        self.phase += motion.distance / (STRIDE_FACTOR * block_size as f32) * PI;
+        let stepped = (self.phase / PI).floor() > before;
+        if self.phase >= TAU { self.phase -= TAU; }
+        if !stepped { return None; }
+
+        let foot = self.next_foot;
+        self.next_foot = if foot == Foot::Left { Foot::Right } else { Foot::Left };
+        Some(foot)
+    }
+
+    /// Copia del jugador tal como se dibuja, con los efectos aplicados.
+    pub fn view(&self, player: &Player, block_size: usize) -> Player {
+        let bs = block_size as f32;
+        let bob = BOB_FACTOR * bs * self.settings.head_bob.amount() * self.walking * self.phase.sin().abs();
+        let dip = self.since_landing.map_or(0.0, |t| {
+            DIP_FACTOR * bs * self.settings.landing_dip.amount() * (PI * t / DIP_SECS).sin()
+        });
+
+        let mut view = *player;
+        view.z += bob - dip;
+        view.a += self.sway;
+        // el ojo no puede salir por encima de las paredes ni meterse en el suelo
+        let eye = view.eye_height(block_size);
+        view.z -= eye - eye.clamp(0.1 * bs, 0.95 * bs);
+        view
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Effect;
+
+    const BS: usize = 64;
+
+    #[test]
+    fn steps_alternate_and_match_the_bob() {
+        let mut cam = Camera::new(CameraSettings::default());
+        let player = Player::new(Vector2::zero(), 0.0);
+        let stride = STRIDE_FACTOR * BS as f32;
+
+        // cinco pasos a 60 fps caminando a 220 px/s
+        let mut steps = Vec::new();
+        let mut walked = 0.0;
+        while walked < 5.0 * stride + 1.0 {
+            let motion = Motion { distance: 220.0 / 60.0, strafe: 0.0 };
+            walked += motion.distance;
+            if let Some(foot) = cam.update(&player, motion, BS, 1.0 / 60.0) {
+                // al pisar, el balanceo está en su punto más bajo
+                assert!(cam.view(&player, BS).z < 0.25 * BOB_FACTOR * BS as f32);
+                steps.push(foot);
+            }
+        }
+        assert_eq!(steps, [Foot::Left, Foot::Right, Foot::Left, Foot::Right, Foot::Left]);
+
+        let mut still = Camera::new(CameraSettings { head_bob: Effect { enabled: false, strength: 1.0 }, ..CameraSettings::default() });
+        for _ in 0..30 { still.update(&player, Motion { distance: 4.0, strafe: 0.0 }, BS, 1.0 / 60.0); }
+        assert_eq!(still.view(&player, BS).z, 0.0);
+    }
+
+    #[test]
+    fn landing_dips_then_recovers() {
+        let mut cam = Camera::new(CameraSettings::default());
+        let mut player = Player::new(Vector2::zero(), 0.0);
+        player.z = 10.0;
+        cam.update(&player, Motion::default(), BS, 1.0 / 60.0);
+        player.z = 0.0;
+        cam.update(&player, Motion::default(), BS, 0.1);
+        assert!(cam.view(&player, BS).z < -1.0);
+
+        cam.update(&player, Motion::default(), BS, DIP_SECS);
+        assert_eq!(cam.view(&player, BS).z, 0.0);
+    }
+}
@@ -1,61 +1,159 @@
-use raylib::color::Color;
-use crate::framebuffer::Framebuffer;
-use crate::maze::Maze;
+use raylib::prelude::Vector2;
+
+use crate::door::{Doors, is_door, spans_x};
+use crate::maze::{Maze, is_walkable};
 use crate::player::Player;
 
+/// Cara de la celda de pared contra la que chocó el rayo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Face { North, South, East, West }
+
 pub struct Intersect {
     pub distance: f32,
     pub impact: char,
-    pub hit_x: usize,
-    pub hit_y: usize,
+    pub hit_x: f32,
+    pub hit_y: f32,
+    pub face: Face,
+    /// Coordenada horizontal de textura (0..1) sobre la cara golpeada.
+    pub tex_u: f32,
 }
 
 #[inline]
 fn is_wall(c: char) -> bool {
-    !(c == ' ' || c == 'g' || c == 'p' || c == 'b' || c == 'V')
+    !is_walkable(c)
 }
 
+/// Recorre la rejilla celda por celda (DDA) hasta la primera pared. Las
+/// puertas se ven hundidas a mitad de su casilla y, mientras se abren, el
+/// rayo pasa por el hueco que dejan.
 pub fn cast_ray(
-    framebuffer: &mut Framebuffer,
     maze: &Maze,
     player: &Player,
     a: f32,
     block_size: usize,
-    draw_line: bool,
+    doors: &Doors,
 ) -> Intersect {
-    let mut d: f32 = 0.0;
-    const STEP: f32 = 2.0;
+    cast_ray_from(maze, player.pos, a, block_size, doors)
+}
 
-    framebuffer.set_current_color(Color::WHITESMOKE);
+/// Igual que `cast_ray`, desde cualquier punto del mundo.
+pub fn cast_ray_from(
+    maze: &Maze,
+    origin: Vector2,
+    a: f32,
+    block_size: usize,
+    doors: &Doors,
+) -> Intersect {
+    let bs = block_size as f32;
+    let (dir_x, dir_y) = (a.cos(), a.sin());
 
-    loop {
-        let x_f = player.pos.x + d * a.cos();
-        let y_f = player.pos.y + d * a.sin();
-        let xi = x_f as isize;
-        let yi = y_f as isize;
+    // posición en unidades de celda
+    let gx = origin.x / bs;
+    let gy = origin.y / bs;
 
-        if xi < 0 || yi < 0 {
-            let hx = if xi < 0 { 0 } else { xi as usize };
-            let hy = if yi < 0 { 0 } else { yi as usize };
-            return Intersect { distance: d.max(1.0), impact: '#', hit_x: hx, hit_y: hy };
-        }
+    let mut map_x = gx.floor() as isize;
+    let mut map_y = gy.floor() as isize;
+
+    let delta_x = if dir_x == 0.0 { f32::INFINITY } else { (1.0 / dir_x).abs() };
+    let delta_y = if dir_y == 0.0 { f32::INFINITY } else { (1.0 / dir_y).abs() };
 
-        let x = xi as usize;
-        let y = yi as usize;
+    let (step_x, mut side_x) = if dir_x < 0.0 {
+        (-1, (gx - map_x as f32) * delta_x)
+    } else {
+        (1, (map_x as f32 + 1.0 - gx) * delta_x)
+    };
+    let (step_y, mut side_y) = if dir_y < 0.0 {
+        (-1, (gy - map_y as f32) * delta_y)
+    } else {
+        (1, (map_y as f32 + 1.0 - gy) * delta_y)
+    };
 
-        let i = x / block_size;
-        let j = y / block_size;
+    let (t, face, impact) = loop {
+        let crossed_x = side_x < side_y;
+        let t = if crossed_x {
+            let t = side_x;
+            side_x += delta_x;
+            map_x += step_x;
+            t
+        } else {
+            let t = side_y;
+            side_y += delta_y;
+            map_y += step_y;
+            t
+        };
 
+        let face = match (crossed_x, step_x > 0, step_y > 0) {
+            (true, true, _)   => Face::West,
+            (true, false, _)  => Face::East,
+            (false, _, true)  => Face::North,
+            (false, _, false) => Face::South,
+        };
+
+        if map_x < 0 || map_y < 0 {
+            break (t, face, '#');
+        }
+        let (i, j) = (map_x as usize, map_y as usize);
         if j >= maze.len() || i >= maze[j].len() {
-            return Intersect { distance: d.max(1.0), impact: '#', hit_x: x, hit_y: y };
+            break (t, face, '#');
         }
 
         let tile = maze[j][i];
-        if is_wall(tile) {
-            return Intersect { distance: d.max(1.0), impact: tile, hit_x: x, hit_y: y };
+        if is_door(tile) {
+            if let Some(hit) = door_hit(maze, (i, j), gx, gy, dir_x, dir_y, doors) {
+                break hit;
+            }
+        } else if is_wall(tile) {
+            break (t, face, tile);
         }
+    };
+
+    let hit_gx = gx + dir_x * t;
+    let hit_gy = gy + dir_y * t;
+
+    // u crece de izquierda a derecha vista desde fuera de la pared; en las
+    // puertas se corre con la hoja
+    let shift = if is_door(impact) { doors.openness((hit_gx as usize, hit_gy as usize)) } else { 0.0 };
+    let tex_u = match face {
+        Face::North => 1.0 - hit_gx.fract() + shift,
+        Face::South => hit_gx.fract() - shift,
+        Face::West  => hit_gy.fract() - shift,
+        Face::East  => 1.0 - hit_gy.fract() + shift,
+    };
 
-        if draw_line { framebuffer.set_pixel(x as u32, y as u32); }
-        d += STEP;
+    Intersect {
+        distance: t * bs,
+        impact,
+        hit_x: hit_gx * bs,
+        hit_y: hit_gy * bs,
+        face,
+        tex_u: tex_u.clamp(0.0, 1.0),
     }
-}
\ No newline at end of file
+}
+
+/// Choque con la hoja de la puerta de `cell`, un plano a mitad de la casilla
+/// que se desliza hacia +x (o +y) al abrirse. `None` si el rayo pasa por el
+/// hueco o sale de la casilla sin cruzar el plano.
+fn door_hit(
+    maze: &Maze,
+    (i, j): (usize, usize),
+    gx: f32,
+    gy: f32,
+    dir_x: f32,
+    dir_y: f32,
+    doors: &Doors,
+) -> Option<(f32, Face, char)> {
+    let open = doors.openness((i, j));
+    let (t, along, face) = if spans_x(maze, (i, j)) {
+        if dir_y == 0.0 { return None; }
+        let t = (j as f32 + 0.5 - gy) / dir_y;
+        let face = if dir_y > 0.0 { Face::North } else { Face::South };
+        (t, gx + dir_x * t - i as f32, face)
+    } else {
+        if dir_x == 0.0 { return None; }
+        let t = (i as f32 + 0.5 - gx) / dir_x;
+        let face = if dir_x > 0.0 { Face::West } else { Face::East };
+        (t, gy + dir_y * t - j as f32, face)
+    };
+    if t < 0.0 || !(open..1.0).contains(&along) { return None; }
+    Some((t, face, maze[j][i]))
+}
@@ -0,0 +1,178 @@
+use raylib::prelude::*;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::framebuffer::Framebuffer;
+use crate::pause::MenuView;
+
+/// Datos del marcador que se dibuja sobre el frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hud {
+    pub candies_collected: u32,
+    pub candies_total: u32,
+    pub remaining_secs: i32,
+    /// Línea extra bajo el marcador (nivel y semilla en el modo infinito).
+    pub caption: Option<String>,
+    /// Carga de la linterna (0 a 1) en los niveles a oscuras.
+    pub battery: Option<f32>,
+}
+
+/// Destino al que se entrega cada frame terminado.
+pub trait Display {
+    fn present(&mut self, fb: &Framebuffer, hud: Option<&Hud>, status: Option<&str>);
+}
+
+/// Textura de la ventana donde se sube cada frame del framebuffer. Se crea en
+/// el primer uso y se vuelve a crear solo si cambia el tamaño del framebuffer.
+#[derive(Default)]
+pub struct ScreenTexture(Option<Texture2D>);
+
+impl ScreenTexture {
+    /// Sube el frame; va antes de `begin_drawing`, que toma la ventana.
+    pub fn upload(&mut self, window: &mut RaylibHandle, thread: &RaylibThread, fb: &Framebuffer) {
+        let stale = match self.0.as_ref() {
+            Some(tex) => tex.width != fb.width as i32 || tex.height != fb.height as i32,
+            None => true,
+        };
+        if stale {
+            // gen_image_color produce RGBA8, el mismo formato que el framebuffer
+            let image = Image::gen_image_color(fb.width as i32, fb.height as i32, Color::BLACK);
+            self.0 = window.load_texture_from_image(thread, &image).ok();
+        }
+        if let Some(tex) = self.0.as_mut() && tex.update_texture(&fb.color_buffer).is_err() {
+            self.0 = None;
+        }
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle<'_>) {
+        if let Some(tex) = &self.0 { d.draw_texture(tex, 0, 0, Color::WHITE); }
+    }
+}
+
+/// Reloj, dulces y FPS en la esquina superior derecha de una pantalla de `screen_w`.
+pub fn draw_hud(d: &mut RaylibDrawHandle<'_>, screen_w: i32, hud: &Hud) {
+    let secs = hud.remaining_secs.max(0);
+    let m = secs / 60;
+    let s = secs % 60;
+    let clock_text = format!("{:02}:{:02}", m, s);
+    let candies_text = format!("Candies: {}/{}", hud.candies_collected, hud.candies_total);
+    let fps_text = format!("FPS: {}", d.get_fps());
+
+    let font_size = 20;
+    let pad = 10;
+
+    let w_clock   = d.measure_text(&clock_text,   font_size);
+    let w_candies = d.measure_text(&candies_text, font_size);
+    let w_fps     = d.measure_text(&fps_text,     font_size);
+    let block_w = *[w_clock, w_candies, w_fps].iter().max().unwrap_or(&0);
+
+    let x = screen_w - block_w - pad;
+    let mut y = 10;
+
+    d.draw_text(&clock_text,   x, y, font_size, Color::YELLOW);
+    y += font_size + 4;
+    d.draw_text(&candies_text, x, y, font_size, Color::WHITE);
+    if let Some(charge) = hud.battery {
+        draw_battery(d, x - pad, y, font_size, charge);
+    }
+    y += font_size + 4;
+    d.draw_text(&fps_text,     x, y, font_size, Color::WHITE);
+
+    if let Some(caption) = &hud.caption {
+        let w = d.measure_text(caption, font_size);
+        y += font_size + 4;
+        d.draw_text(caption, screen_w - w - pad, y, font_size, Color::PINK);
+    }
+}
+
+/// Mensaje grande centrado sobre una franja oscura.
+pub fn draw_status(d: &mut RaylibDrawHandle<'_>, screen_w: i32, screen_h: i32, status_text: &str) {
+    let banner_font_size = 40;
+    let text_width = d.measure_text(status_text, banner_font_size);
+    let text_height = banner_font_size;
+    let center_x = (screen_w - text_width) / 2;
+    let center_y = (screen_h - text_height) / 2;
+    let padding = 20;
+    d.draw_rectangle(
+        center_x - padding/2,
+        center_y - padding/2,
+        text_width + padding,
+        text_height + padding,
+        Color::new(0, 0, 0, 180)
+    );
+    d.draw_text(
+        status_text,
+        center_x,
+        center_y,
+        banner_font_size,
+        Color::WHITE
+    );
+}
+
+/// Menú centrado sobre el frame oscurecido; la opción elegida va en amarillo.
+pub fn draw_menu(d: &mut RaylibDrawHandle<'_>, screen_w: i32, screen_h: i32, menu: &MenuView) {
+    let (title_size, font_size) = (40, 24);
+    let row_h = font_size + 12;
+    let panel_w = screen_w / 2;
+    let panel_h = title_size + 30 + row_h * menu.items.len() as i32 + 20;
+    let (x, y) = ((screen_w - panel_w) / 2, (screen_h - panel_h) / 2);
+
+    d.draw_rectangle(0, 0, screen_w, screen_h, Color::new(0, 0, 0, 120));
+    d.draw_rectangle(x, y, panel_w, panel_h, Color::new(0, 0, 0, 200));
+    let title_w = d.measure_text(menu.title, title_size);
+    d.draw_text(menu.title, (screen_w - title_w) / 2, y + 15, title_size, Color::PINK);
+
+    for (k, item) in menu.items.iter().enumerate() {
+        let color = if k == menu.selected { Color::YELLOW } else { Color::WHITE };
+        let row_y = y + title_size + 30 + row_h * k as i32;
+        d.draw_text(item, x + 30, row_y, font_size, color);
+    }
+}
+
+/// Pila de la linterna a la izquierda de (right, y), llena según `charge`.
+fn draw_battery(d: &mut RaylibDrawHandle<'_>, right: i32, y: i32, h: i32, charge: f32) {
+    let (w, cap) = (48, 4);
+    let x = right - w - cap;
+    let color = if charge > 0.5 { Color::LIME } else if charge > 0.2 { Color::GOLD } else { Color::RED };
+    d.draw_rectangle(x + w, y + h / 4, cap, h / 2, Color::WHITE);
+    d.draw_rectangle(x + 2, y + 2, ((w - 4) as f32 * charge.clamp(0.0, 1.0)) as i32, h - 4, color);
+    d.draw_rectangle_lines(x, y, w, h, Color::WHITE);
+}
+
+/// Guarda el último frame en memoria; sirve para pruebas y herramientas sin ventana.
+/// El HUD y el aviso no se dibujan: sin ventana no hay fuente.
+#[derive(Default)]
+pub struct OffscreenDisplay {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Color>,
+}
+
+impl OffscreenDisplay {
+    pub fn new() -> Self { Self::default() }
+
+    /// Escribe el frame como PPM binario (P6); el canal alfa se descarta.
+    pub fn save_ppm(&self, path: &str) -> io::Result<()> {
+        write_ppm(path, self.width, self.height, &self.pixels)
+    }
+}
+
+impl Display for OffscreenDisplay {
+    fn present(&mut self, fb: &Framebuffer, _hud: Option<&Hud>, _status: Option<&str>) {
+        self.width = fb.width;
+        self.height = fb.height;
+        self.pixels.clear();
+        self.pixels.extend(
+            fb.color_buffer.chunks_exact(4).map(|p| Color::new(p[0], p[1], p[2], p[3])),
+        );
+    }
+}
+
+pub fn write_ppm(path: &str, width: u32, height: u32, pixels: &[Color]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write!(out, "P6\n{} {}\n255\n", width, height)?;
+    for c in pixels {
+        out.write_all(&[c.r, c.g, c.b])?;
+    }
+    out.flush()
+}
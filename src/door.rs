@@ -0,0 +1,184 @@
+// door.rs
+// Puertas de color ('R', 'A', 'M') y sus llaves ('r', 'a', 'm'). Una puerta es
+// pared hasta que el jugador llega a ella con su llave; entonces se desliza
+// dentro del muro y, ya abierta, su casilla pasa a ser espacio libre.
+use raylib::prelude::*;
+
+use crate::maze::{Maze, is_walkable};
+use crate::pathfind::{Cell, Pathfinder};
+
+pub const KEY_TILES: [char; 3] = ['r', 'a', 'm'];
+pub const DOOR_TILES: [char; 3] = ['R', 'A', 'M'];
+
+/// Segundos que tarda una puerta en abrirse del todo.
+const OPEN_SECS: f32 = 1.0;
+/// Distancia (en bloques) desde la que el jugador abre una puerta o ve que está cerrada.
+const REACH_FACTOR: f32 = 1.5;
+
+#[inline]
+pub fn is_door(c: char) -> bool { DOOR_TILES.contains(&c) }
+
+#[inline]
+pub fn is_key(c: char) -> bool { KEY_TILES.contains(&c) }
+
+/// Puerta que abre la llave `key`.
+#[inline]
+pub fn door_for(key: char) -> char { key.to_ascii_uppercase() }
+
+/// Nombre del color de una llave o puerta, para los mensajes.
+pub fn color_name(c: char) -> &'static str {
+    match c.to_ascii_lowercase() {
+        'r' => "roja",
+        'a' => "azul",
+        _ => "morada",
+    }
+}
+
+/// Color de una puerta en el minimapa.
+pub fn map_color(c: char) -> Color {
+    match c.to_ascii_lowercase() {
+        'r' => Color::new(220, 60, 70, 255),
+        'a' => Color::new(70, 110, 230, 255),
+        _ => Color::new(160, 80, 200, 255),
+    }
+}
+
+/// Si la hoja de la puerta en `cell` corre a lo largo de x (muros a izquierda
+/// y derecha); si no, corre a lo largo de y.
+pub fn spans_x(maze: &Maze, (i, j): Cell) -> bool {
+    let solid = |i: Option<usize>| i.and_then(|i| maze[j].get(i)).is_none_or(|&c| !is_walkable(c));
+    solid(i.checked_sub(1)) && solid(Some(i + 1))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Door {
+    cell: Cell,
+    tile: char,
+    /// De 0 (cerrada) a 1 (abierta).
+    open: f32,
+    opening: bool,
+}
+
+/// Estado de las puertas de un nivel y llaves que lleva el jugador.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Doors {
+    doors: Vec<Door>,
+    keys: Vec<char>,
+}
+
+impl Doors {
+    pub fn from_maze(maze: &Maze) -> Doors {
+        let mut doors = Vec::new();
+        for (j, row) in maze.iter().enumerate() {
+            for (i, &c) in row.iter().enumerate() {
+                if is_door(c) { doors.push(Door { cell: (i, j), tile: c, open: 0.0, opening: false }); }
+            }
+        }
+        Doors { doors, keys: Vec::new() }
+    }
+
+    /// Cuánto se ha abierto la puerta de `cell`; 0 si no se conoce.
+    pub fn openness(&self, cell: Cell) -> f32 {
+        self.doors.iter().find(|d| d.cell == cell).map_or(0.0, |d| d.open)
+    }
+
+    pub fn give_key(&mut self, key: char) {
+        if !self.keys.contains(&key) { self.keys.push(key); }
+    }
+
+    pub fn has_key_for(&self, door: char) -> bool {
+        self.keys.iter().any(|&k| door_for(k) == door)
+    }
+
+    /// Empieza a abrir las puertas cercanas cuya llave lleva el jugador y
+    /// avanza su animación. Devuelve las casillas que terminaron de abrirse
+    /// en este frame; a partir de ahí se pueden pisar.
+    pub fn update(&mut self, player: Vector2, block_size: usize, dt: f32) -> Vec<Cell> {
+        let reach = REACH_FACTOR * block_size as f32;
+        let keys = &self.keys;
+        let mut opened = Vec::new();
+        for door in self.doors.iter_mut().filter(|d| d.open < 1.0) {
+            if !door.opening && keys.iter().any(|&k| door_for(k) == door.tile) {
+                door.opening = distance(door.cell, player, block_size) < reach;
+            }
+            if !door.opening { continue; }
+            door.open = (door.open + dt / OPEN_SECS).min(1.0);
+            if door.open >= 1.0 { opened.push(door.cell); }
+        }
+        opened
+    }
+
+    /// Puerta cerrada junto al jugador para la que no tiene llave.
+    pub fn locked_near(&self, player: Vector2, block_size: usize) -> Option<char> {
+        let reach = REACH_FACTOR * block_size as f32;
+        self.doors
+            .iter()
+            .find(|d| !d.opening && !self.has_key_for(d.tile) && distance(d.cell, player, block_size) < reach)
+            .map(|d| d.tile)
+    }
+}
+
+fn distance((i, j): Cell, p: Vector2, block_size: usize) -> f32 {
+    let bs = block_size as f32;
+    ((i as f32 + 0.5) * bs - p.x).hypot((j as f32 + 0.5) * bs - p.y)
+}
+
+/// Caminos del laberinto con abiertas las puertas a las que se puede llegar
+/// con las llaves alcanzables desde `from`, repitiendo hasta que no se abra
+/// ninguna más. Sirve para revisar que un nivel con puertas se pueda terminar.
+pub fn unlock_reachable(maze: &Maze, from: Cell) -> Pathfinder {
+    let mut paths = Pathfinder::new(maze);
+    let mut locked: Vec<(Cell, char)> = Doors::from_maze(maze).doors.iter().map(|d| (d.cell, d.tile)).collect();
+    loop {
+        let dist = paths.distance_field(from);
+        let mut keys = Vec::new();
+        for (j, row) in maze.iter().enumerate() {
+            for (i, &c) in row.iter().enumerate() {
+                if is_key(c) && dist.get((i, j)).is_some() { keys.push(door_for(c)); }
+            }
+        }
+        let (open, rest): (Vec<_>, Vec<_>) = locked.into_iter().partition(|(_, tile)| keys.contains(tile));
+        if open.is_empty() { return paths; }
+        for (cell, _) in open { paths.set_walkable(cell, true); }
+        locked = rest;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|r| r.chars().collect()).collect()
+    }
+
+    #[test]
+    fn doors_open_only_with_their_key() {
+        let m = maze(&[
+            "+------+",
+            "|p r | |",
+            "+-A+RR+|",
+            "|      |",
+            "+------+",
+        ]);
+        // con la roja se pasa a la fila de abajo; la azul no está en el nivel
+        let paths = unlock_reachable(&m, (1, 1));
+        assert!(paths.is_walkable((4, 2)) && paths.is_walkable((5, 2)));
+        assert!(!paths.is_walkable((2, 2)));
+        assert!(spans_x(&m, (4, 2)) && spans_x(&m, (2, 2)));
+
+        let mut doors = Doors::from_maze(&m);
+        let bs = 64;
+        let near = Vector2::new(4.5 * 64.0, 1.5 * 64.0);
+        assert_eq!(doors.locked_near(near, bs), Some('R'));
+        assert!(doors.update(near, bs, 0.5).is_empty());
+        assert_eq!(doors.openness((4, 2)), 0.0);
+
+        doors.give_key('r');
+        assert_eq!(doors.locked_near(near, bs), None);
+        assert!(doors.update(near, bs, 0.6).is_empty());
+        assert!(doors.openness((4, 2)) > 0.5);
+        assert_eq!(doors.update(near, bs, 0.6), vec![(4, 2), (5, 2)]);
+        assert_eq!(doors.openness((2, 2)), 0.0, "la azul sigue cerrada");
+    }
+}
@@ -0,0 +1,72 @@
+// endless.rs
+// Modo infinito: cada nivel es un laberinto generado. Toda la partida sale de
+// una sola semilla, así que con la semilla se puede repetir la misma.
+use crate::maze::{Maze, analyze};
+use crate::mazegen::{Algorithm, GenOptions, Rng, generate};
+
+/// Casillas que recorre el jugador por segundo (MOVE_SPEED / BLOCK_SIZE).
+const TILES_PER_SEC: f32 = 220.0 / 64.0;
+const MAX_WIDTH: usize = 30;
+const MAX_HEIGHT: usize = 20;
+const MAX_CANDIES: usize = 16;
+
+pub struct Level { pub number: u32, pub candies_needed: u32, pub duration_secs: i32 }
+
+/// Semilla nueva a partir del reloj, para cuando no se pide una concreta.
+pub fn random_seed() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    // se acorta para que sea fácil de dictar
+    Rng::new(nanos).next_u64() % 1_000_000
+}
+
+/// Nivel `number` (desde 1) de la partida `seed`: el laberinto crece, hay más
+/// dulces, menos atajos y menos margen de tiempo a medida que se avanza.
+pub fn endless_level(seed: u64, number: u32) -> (Level, Maze) {
+    let n = number.max(1) as usize;
+    let mut rng = Rng::new(seed ^ (n as u64).wrapping_mul(0xA24B_AED4_963E_E407));
+
+    let opts = GenOptions {
+        width: (5 + n).min(MAX_WIDTH),
+        height: (4 + n / 2).min(MAX_HEIGHT),
+        algorithm: Algorithm::ALL[rng.below(Algorithm::ALL.len())],
+        seed: rng.next_u64(),
+        braid: (0.5 - 0.05 * n as f32).max(0.0),
+        collectibles: (2 + n).min(MAX_CANDIES),
+        ..GenOptions::default()
+    };
+    let maze = generate(&opts);
+
+    // el tiempo se calcula sobre la ruta real, con un margen que se va cerrando
+    let route = analyze(&maze).ok().and_then(|r| r.route_length).unwrap_or(0) as f32;
+    let slack = (3.0 - 0.15 * n as f32).max(1.5);
+    let duration_secs = (route / TILES_PER_SEC * slack).ceil() as i32 + 10;
+
+    let level = Level { number, candies_needed: opts.collectibles as u32, duration_secs };
+    (level, maze)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::validate;
+
+    #[test]
+    fn runs_are_reproducible_and_get_harder() {
+        let (a, maze_a) = endless_level(1234, 3);
+        let (b, maze_b) = endless_level(1234, 3);
+        assert_eq!(maze_a, maze_b);
+        assert_eq!((a.candies_needed, a.duration_secs), (b.candies_needed, b.duration_secs));
+        assert_ne!(endless_level(1235, 3).1, maze_a);
+
+        let mut prev_candies = 0;
+        for number in 1..=12 {
+            let (level, maze) = endless_level(99, number);
+            validate(&maze, true).unwrap_or_else(|e| panic!("nivel {}: {}", number, e));
+            assert!(level.candies_needed >= prev_candies);
+            assert!(level.duration_secs > 10);
+            prev_candies = level.candies_needed;
+        }
+    }
+}
@@ -0,0 +1,230 @@
+// enemy.rs
+// Ositos de goma ('E' en el laberinto) que patrullan los pasillos y persiguen
+// al jugador cuando lo ven. Caminan de centro a centro de casilla; al
+// perseguir siguen el camino más corto por la cuadrícula hasta donde lo vieron
+// por última vez (con el campo de flujo de `pathfind`) y, si lo pierden,
+// vuelven a patrullar.
+use raylib::prelude::Vector2;
+
+use crate::caster::cast_ray_from;
+use crate::door::Doors;
+use crate::maze::Maze;
+use crate::mazegen::Rng;
+use crate::pathfind::{Cell, Pathfinder};
+use crate::sprite::Sprite;
+
+pub const ENEMY_TILE: char = 'E';
+
+/// Distancia (en bloques) a la que un osito alcanza al jugador.
+const CONTACT_FACTOR: f32 = 0.45;
+/// Segundos que se queda quieto después de alcanzarlo, para dejarlo escapar.
+const STUN_SECS: f32 = 2.0;
+
+/// Comportamiento de los enemigos de un nivel, con las distancias en píxeles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnemyConfig {
+    /// Velocidad patrullando, en píxeles por segundo.
+    pub speed: f32,
+    /// Velocidad persiguiendo; menor que la del jugador para poder huir.
+    pub chase_speed: f32,
+    /// Distancia máxima a la que ve al jugador.
+    pub sight: f32,
+    /// Segundos que se restan al reloj cuando alcanza al jugador.
+    pub penalty: f32,
+}
+
+impl EnemyConfig {
+    pub fn new(block_size: usize) -> EnemyConfig {
+        let bs = block_size as f32;
+        EnemyConfig { speed: 1.5 * bs, chase_speed: 2.6 * bs, sight: 7.0 * bs, penalty: 10.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode { Patrol, Chase }
+
+#[derive(Debug, Clone)]
+pub struct Enemy {
+    pub pos: Vector2,
+    pub mode: Mode,
+    /// Casilla hacia la que camina.
+    target: Cell,
+    /// Casilla de la que viene, para no dar media vuelta al patrullar.
+    from: Cell,
+    /// Casilla donde vio al jugador por última vez.
+    last_seen: Option<Cell>,
+    stunned: f32,
+    rng: Rng,
+}
+
+#[inline]
+fn cell_of(p: Vector2, block_size: usize) -> Cell {
+    ((p.x.max(0.0) as usize) / block_size, (p.y.max(0.0) as usize) / block_size)
+}
+
+#[inline]
+fn center((i, j): Cell, block_size: usize) -> Vector2 {
+    let bs = block_size as f32;
+    Vector2::new((i as f32 + 0.5) * bs, (j as f32 + 0.5) * bs)
+}
+
+impl Enemy {
+    /// Osito parado en el centro de `sprite`, la casilla donde estaba su 'E'.
+    pub fn from_sprite(sprite: &Sprite, block_size: usize) -> Enemy {
+        let pos = Vector2::new(sprite.x, sprite.y);
+        let cell = cell_of(pos, block_size);
+        Enemy {
+            pos,
+            mode: Mode::Patrol,
+            target: cell,
+            from: cell,
+            last_seen: None,
+            stunned: 0.0,
+            rng: Rng::new(((cell.0 as u64) << 32) ^ cell.1 as u64),
+        }
+    }
+
+    /// Billboard para dibujarlo con el resto de los sprites.
+    pub fn sprite(&self) -> Sprite {
+        Sprite { x: self.pos.x, y: self.pos.y, kind: ENEMY_TILE, collected: false }
+    }
+
+    /// Si ve al jugador: cerca y sin paredes en medio. Por una puerta a medio
+    /// abrir ve por el hueco, como el jugador.
+    pub fn sees(&self, maze: &Maze, doors: &Doors, player: Vector2, config: &EnemyConfig, block_size: usize) -> bool {
+        let (dx, dy) = (player.x - self.pos.x, player.y - self.pos.y);
+        let dist = (dx * dx + dy * dy).sqrt();
+        if dist > config.sight { return false; }
+        if dist < 1.0 { return true; }
+        cast_ray_from(maze, self.pos, dy.atan2(dx), block_size, doors).distance >= dist
+    }
+
+    /// Avanza un frame. Devuelve `true` si en este frame alcanzó al jugador.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        maze: &Maze,
+        doors: &Doors,
+        paths: &Pathfinder,
+        player: Vector2,
+        config: &EnemyConfig,
+        block_size: usize,
+        dt: f32,
+    ) -> bool {
+        if self.stunned > 0.0 {
+            self.stunned -= dt;
+            return false;
+        }
+        if self.sees(maze, doors, player, config, block_size) {
+            self.mode = Mode::Chase;
+            self.last_seen = Some(cell_of(player, block_size));
+        }
+
+        let speed = if self.mode == Mode::Chase { config.chase_speed } else { config.speed };
+        let mut budget = speed * dt;
+        while budget > 0.0 {
+            let c = center(self.target, block_size);
+            let (dx, dy) = (c.x - self.pos.x, c.y - self.pos.y);
+            let len = (dx * dx + dy * dy).sqrt();
+            if len > budget {
+                self.pos.x += dx / len * budget;
+                self.pos.y += dy / len * budget;
+                break;
+            }
+            // llegó al centro: elige la casilla siguiente
+            self.pos = c;
+            budget -= len;
+            let here = self.target;
+            let next = self.next_cell(paths, here);
+            if next == here { break; }
+            self.from = here;
+            self.target = next;
+        }
+
+        let (dx, dy) = (player.x - self.pos.x, player.y - self.pos.y);
+        let caught = (dx * dx + dy * dy).sqrt() < CONTACT_FACTOR * block_size as f32;
+        if caught { self.stunned = STUN_SECS; }
+        caught
+    }
+
+    fn next_cell(&mut self, paths: &Pathfinder, here: Cell) -> Cell {
+        if let Some(goal) = self.last_seen {
+            // sigue el flujo hacia donde lo vio; el campo queda en caché
+            if let Some(cell) = paths.flow_field(goal).next(here) { return cell; }
+            // llegó y no lo ve: vuelve a patrullar
+            self.last_seen = None;
+            self.mode = Mode::Patrol;
+        }
+
+        let options: Vec<Cell> = paths.neighbours(here).collect();
+        let forward: Vec<Cell> = options.iter().copied().filter(|&c| c != self.from).collect();
+        match (forward.len(), options.len()) {
+            (0, 0) => here,
+            (0, _) => self.from,
+            (n, _) => forward[self.rng.below(n)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BS: usize = 64;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|r| r.chars().collect()).collect()
+    }
+
+    #[test]
+    fn chases_in_sight_and_patrols_behind_walls() {
+        let m = maze(&[
+            "+-------+",
+            "|       |",
+            "+-+-+-+ |",
+            "|       |",
+            "+-------+",
+        ]);
+        let paths = Pathfinder::new(&m);
+        let doors = Doors::from_maze(&m);
+        let config = EnemyConfig::new(BS);
+        let mut bear = Enemy::from_sprite(&Sprite::new_cell(1, 1, BS, ENEMY_TILE), BS);
+
+        // el jugador en la fila de abajo no se ve
+        let hidden = center((1, 3), BS);
+        assert!(!bear.sees(&m, &doors, hidden, &config, BS));
+        for _ in 0..30 { bear.update(&m, &doors, &paths, hidden, &config, BS, 1.0 / 60.0); }
+        assert_eq!(bear.mode, Mode::Patrol);
+        assert_eq!(bear.pos.y, center((1, 1), BS).y);
+
+        // en el mismo pasillo lo ve, lo sigue por la vuelta y lo alcanza
+        let mut player = center((6, 1), BS);
+        let mut caught = false;
+        for frame in 0..600 {
+            if frame == 60 { player = center((3, 3), BS); }
+            if bear.update(&m, &doors, &paths, player, &config, BS, 1.0 / 60.0) { caught = true; break; }
+        }
+        assert!(caught);
+        assert_eq!(bear.mode, Mode::Chase);
+        assert!(!bear.update(&m, &doors, &paths, player, &config, BS, 1.0 / 60.0), "queda aturdido tras alcanzarlo");
+    }
+
+    #[test]
+    fn doors_block_sight_until_they_open() {
+        let m = maze(&[
+            "+-------+",
+            "|   R   |",
+            "+-------+",
+        ]);
+        let config = EnemyConfig::new(BS);
+        let bear = Enemy::from_sprite(&Sprite::new_cell(1, 1, BS, ENEMY_TILE), BS);
+        let player = center((6, 1), BS);
+
+        let mut doors = Doors::from_maze(&m);
+        assert!(!bear.sees(&m, &doors, player, &config, BS), "la puerta cerrada lo tapa");
+
+        doors.give_key('r');
+        while doors.update(center((5, 1), BS), BS, 0.25).is_empty() {}
+        assert!(bear.sees(&m, &doors, player, &config, BS), "abierta se ve a través");
+    }
+}
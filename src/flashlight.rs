@@ -0,0 +1,136 @@
+// flashlight.rs
+// Linterna del jugador para niveles a oscuras: solo se ve dentro de un cono
+// delante de la cámara. La pila se gasta con el tiempo y se recarga con las
+// pilas 'B' del laberinto; con poca carga la luz baja y parpadea.
+use raylib::prelude::Color;
+
+use crate::mazegen::Rng;
+
+/// Carga por debajo de la cual la luz empieza a bajar y a parpadear más.
+const LOW_BATTERY: f32 = 0.2;
+/// Cambios de brillo por segundo del parpadeo.
+const FLICKER_HZ: f64 = 12.0;
+
+/// Linterna de un nivel, con la carga que le queda.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Flashlight {
+    /// Medio ángulo del cono, en radianes.
+    pub half_angle: f32,
+    /// Alcance en píxeles del mundo.
+    pub range: f32,
+    /// Luz fuera del cono o con la pila agotada.
+    pub ambient: f32,
+    /// Parpadeo con la pila llena: 0 nada, 1 fuerte.
+    pub flicker: f32,
+    /// Segundos que dura la pila llena.
+    pub capacity: f32,
+    /// Segundos que devuelve cada pila recogida.
+    pub recharge: f32,
+    /// Segundos que le quedan a la pila.
+    pub battery: f32,
+}
+
+impl Flashlight {
+    /// Linterna por defecto, con la pila llena: cono de 50° y seis casillas de alcance.
+    pub fn new(block_size: usize) -> Flashlight {
+        Flashlight {
+            half_angle: 25f32.to_radians(),
+            range: 6.0 * block_size as f32,
+            ambient: 0.04,
+            flicker: 0.0,
+            capacity: 90.0,
+            recharge: 30.0,
+            battery: 90.0,
+        }
+    }
+
+    pub fn drain(&mut self, dt: f32) {
+        self.battery = (self.battery - dt).max(0.0);
+    }
+
+    pub fn recharge_battery(&mut self) {
+        self.battery = (self.battery + self.recharge).min(self.capacity);
+    }
+
+    /// Carga de 0 a 1, para el HUD.
+    pub fn charge(&self) -> f32 {
+        if self.capacity <= 0.0 { return 0.0; }
+        (self.battery / self.capacity).clamp(0.0, 1.0)
+    }
+
+    /// Haz de luz en el instante `time` (segundos), con el parpadeo ya aplicado.
+    pub fn beam(&self, time: f64) -> Beam {
+        let charge = self.charge();
+        let low = (1.0 - charge / LOW_BATTERY).clamp(0.0, 1.0);
+        let flicker = (self.flicker + 0.6 * low).min(1.0);
+        let strength = (charge / LOW_BATTERY).min(1.0) * (1.0 - flicker * flicker_noise(time));
+        Beam { half_angle: self.half_angle, range: self.range, ambient: self.ambient, strength }
+    }
+}
+
+/// Ruido suave entre 0 y 1: valores al azar `FLICKER_HZ` veces por segundo, interpolados.
+fn flicker_noise(time: f64) -> f32 {
+    let t = time.max(0.0) * FLICKER_HZ;
+    let step = t.floor();
+    let f = (t - step) as f32;
+    let at = |n: u64| (Rng::new(n).next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+    let (a, b) = (at(step as u64), at(step as u64 + 1));
+    a + (b - a) * f * f * (3.0 - 2.0 * f)
+}
+
+/// La linterna congelada en un frame: lo que necesita el render.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Beam {
+    pub half_angle: f32,
+    pub range: f32,
+    pub ambient: f32,
+    /// Brillo del haz, de 0 (apagado) a 1.
+    pub strength: f32,
+}
+
+impl Beam {
+    /// Luz a `distance` píxeles y `off_axis` radianes del centro de la vista.
+    /// El borde del cono se difumina en su último 40 %.
+    #[inline]
+    pub fn light(&self, distance: f32, off_axis: f32) -> f32 {
+        if self.strength <= 0.0 || off_axis >= self.half_angle || distance >= self.range {
+            return self.ambient;
+        }
+        let edge = ((self.half_angle - off_axis) / (0.4 * self.half_angle)).min(1.0);
+        let cone = edge * edge * (3.0 - 2.0 * edge);
+        let reach = 1.0 - (distance / self.range).powi(2);
+        self.ambient + (1.0 - self.ambient) * self.strength * cone * reach
+    }
+
+    #[inline]
+    pub fn apply(&self, c: Color, distance: f32, off_axis: f32) -> Color {
+        let k = self.light(distance, off_axis);
+        let ch = |v: u8| (v as f32 * k).round().clamp(0.0, 255.0) as u8;
+        Color::new(ch(c.r), ch(c.g), ch(c.b), c.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cone_fades_with_angle_distance_and_battery() {
+        let mut torch = Flashlight::new(64);
+        let beam = torch.beam(0.0);
+        assert!((beam.light(0.0, 0.0) - 1.0).abs() < 1e-4);
+        assert!(beam.light(64.0, 0.0) > beam.light(256.0, 0.0));
+        assert!(beam.light(64.0, 0.0) > beam.light(64.0, 0.8 * torch.half_angle));
+        assert_eq!(beam.light(64.0, torch.half_angle), torch.ambient);
+        assert_eq!(beam.light(torch.range, 0.0), torch.ambient);
+
+        torch.drain(1000.0);
+        assert_eq!(torch.charge(), 0.0);
+        assert_eq!(torch.beam(3.0).light(0.0, 0.0), torch.ambient);
+
+        torch.recharge_battery();
+        assert!((torch.charge() - 30.0 / 90.0).abs() < 1e-4);
+        for _ in 0..5 { torch.recharge_battery(); }
+        assert_eq!(torch.charge(), 1.0);
+    }
+}
@@ -1,43 +1,117 @@
 use raylib::prelude::*;
+use std::marker::PhantomData;
 
+/// Búfer de color RGBA8 contiguo en memoria; no depende de una ventana abierta.
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
-    pub color_buffer: Image,
+    pub color_buffer: Vec<u8>,
     background_color: Color,
     current_color: Color,
 }
 
+/// Columna vertical del frame, de arriba abajo. Escribe directo en
+/// `color_buffer` y solo toca sus propios píxeles, así que cada hilo puede
+/// pintar las suyas a la vez que los demás.
+pub struct Column<'a> {
+    pub x: u32,
+    width: u32,
+    height: u32,
+    pixels: *mut u8,
+    _frame: PhantomData<&'a mut [u8]>,
+}
+
+// SAFETY: la columna x solo escribe los píxeles (x, 0..height) de un frame
+// prestado en exclusiva, y no se crean dos con la misma x.
+unsafe impl Send for Column<'_> {}
+
+impl Column<'_> {
+    #[inline]
+    pub fn set(&mut self, y: u32, c: Color) {
+        assert!(y < self.height, "fila {} fuera del frame", y);
+        let idx = ((y * self.width + self.x) * 4) as usize;
+        // SAFETY: idx + 4 <= width * height * 4 y ese píxel es de esta columna
+        unsafe { std::ptr::copy_nonoverlapping([c.r, c.g, c.b, c.a].as_ptr(), self.pixels.add(idx), 4) };
+    }
+}
+
+/// Tira de columnas seguidas del frame, para un hilo.
+pub struct Strip<'a> {
+    next: u32,
+    end: u32,
+    width: u32,
+    height: u32,
+    pixels: *mut u8,
+    _frame: PhantomData<&'a mut [u8]>,
+}
+
+// SAFETY: las tiras de un frame no comparten columnas (ver `Column`).
+unsafe impl Send for Strip<'_> {}
+
+impl<'a> Iterator for Strip<'a> {
+    type Item = Column<'a>;
+
+    fn next(&mut self) -> Option<Column<'a>> {
+        if self.next >= self.end { return None; }
+        let x = self.next;
+        self.next += 1;
+        Some(Column { x, width: self.width, height: self.height, pixels: self.pixels, _frame: PhantomData })
+    }
+}
+
 impl Framebuffer {
     pub fn new(width: u32, height: u32) -> Self {
-        let color_buffer = Image::gen_image_color(width as i32, height as i32, Color::BLACK);
-        Self {
-            width, height, color_buffer,
+        let mut fb = Self {
+            width, height,
+            color_buffer: vec![0; (width * height * 4) as usize],
             background_color: Color::BLACK,
             current_color: Color::WHITE,
-        }
+        };
+        fb.clear();
+        fb
     }
 
     pub fn clear(&mut self) {
-        self.color_buffer = Image::gen_image_color(
-            self.width as i32,
-            self.height as i32,
-            self.background_color,
-        );
+        let bg = self.background_color;
+        for px in self.color_buffer.chunks_exact_mut(4) {
+            px.copy_from_slice(&[bg.r, bg.g, bg.b, bg.a]);
+        }
+    }
+
+    #[inline]
+    fn put(&mut self, x: u32, y: u32, c: Color) {
+        let idx = ((y * self.width + x) * 4) as usize;
+        self.color_buffer[idx..idx + 4].copy_from_slice(&[c.r, c.g, c.b, c.a]);
     }
 
     pub fn set_pixel(&mut self, x: u32, y: u32) {
         if x < self.width && y < self.height {
-            self.color_buffer.draw_pixel(x as i32, y as i32, self.current_color);
+            self.put(x, y, self.current_color);
         }
     }
 
     pub fn set_pixel_with_color_i32(&mut self, x: i32, y: i32, color: Color) {
         if x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height {
-            self.color_buffer.draw_pixel(x, y, color);
+            self.put(x as u32, y as u32, color);
         }
     }
 
+    /// Reparte las columnas del frame en tiras de `per_strip` columnas para
+    /// dibujarlas en paralelo, escribiendo directo en `color_buffer`.
+    pub fn strips_mut(&mut self, per_strip: usize) -> impl Iterator<Item = Strip<'_>> {
+        let (width, height) = (self.width, self.height);
+        let pixels = self.color_buffer.as_mut_ptr();
+        let per_strip = per_strip.max(1) as u32;
+        (0..width.div_ceil(per_strip)).map(move |k| Strip {
+            next: k * per_strip,
+            end: ((k + 1) * per_strip).min(width),
+            width,
+            height,
+            pixels,
+            _frame: PhantomData,
+        })
+    }
+
     pub fn draw_circle_filled(&mut self, cx: i32, cy: i32, r: i32, color: Color) {
         let rr = r * r;
         for dy in -r..=r {
@@ -51,72 +125,4 @@ impl Framebuffer {
 
     pub fn set_background_color(&mut self, color: Color) { self.background_color = color; }
     pub fn set_current_color(&mut self, color: Color) { self.current_color = color; }
-
-    pub fn swap_buffers(&self, window: &mut RaylibHandle, rl: &RaylibThread) {
-        self.swap_buffers_with_hud(window, rl, None, None);
-    }
-
-    pub fn swap_buffers_with_hud(
-        &self,
-        window: &mut RaylibHandle,
-        rl: &RaylibThread,
-        hud: Option<(u32, u32, i32)>,
-        status: Option<&str>
-    ) {
-        let fps_val = window.get_fps();
-        if let Ok(tex) = window.load_texture_from_image(rl, &self.color_buffer) {
-            let mut renderer: RaylibDrawHandle<'_> = window.begin_drawing(rl);
-            renderer.clear_background(Color::BLACK);
-            renderer.draw_texture(&tex, 0, 0, Color::WHITE);
-
-            if let Some((candies_collected, candies_total, elapsed_secs)) = hud {
-                let secs = elapsed_secs.clamp(0, 60);
-                let m = secs / 60;
-                let s = secs % 60;
-                let clock_text = format!("{:02}:{:02}", m, s);
-                let candies_text = format!("Candies: {}/{}", candies_collected, candies_total);
-                let fps_text = format!("FPS: {}", fps_val);
-
-                let font_size = 20;
-                let pad = 10;
-
-                let w_clock   = renderer.measure_text(&clock_text,   font_size);
-                let w_candies = renderer.measure_text(&candies_text, font_size);
-                let w_fps     = renderer.measure_text(&fps_text,     font_size);
-                let block_w = *[w_clock, w_candies, w_fps].iter().max().unwrap_or(&0);
-
-                let mut x = self.width as i32 - block_w - pad;
-                let mut y = 10;
-
-                renderer.draw_text(&clock_text,   x, y, font_size, Color::YELLOW);
-                y += font_size + 4;
-                renderer.draw_text(&candies_text, x, y, font_size, Color::WHITE);
-                y += font_size + 4;
-                renderer.draw_text(&fps_text,     x, y, font_size, Color::WHITE);
-            }
-
-            if let Some(status_text) = status {
-                let banner_font_size = 40;
-                let text_width = renderer.measure_text(status_text, banner_font_size);
-                let text_height = banner_font_size;
-                let center_x = (self.width as i32 - text_width) / 2;
-                let center_y = (self.height as i32 - text_height) / 2;
-                let padding = 20;
-                renderer.draw_rectangle(
-                    center_x - padding/2,
-                    center_y - padding/2,
-                    text_width + padding,
-                    text_height + padding,
-                    Color::new(0, 0, 0, 180)
-                );
-                renderer.draw_text(
-                    status_text,
-                    center_x,
-                    center_y,
-                    banner_font_size,
-                    Color::WHITE
-                );
-            }
-        }
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,333 @@
+// Pruebas de imagen de referencia: dibujan frames sin ventana y los comparan
+// contra los PPM guardados en tests/golden. Para regenerarlas tras un cambio
+// intencional del render:
+//
+//     GOLDEN_UPDATE=1 cargo test golden
+use raylib::prelude::*;
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+
+use crate::display::{Display, OffscreenDisplay, write_ppm};
+use crate::door::Doors;
+use crate::framebuffer::Framebuffer;
+use crate::player::Player;
+use crate::levels::{Manifest, MANIFEST_FILE};
+use crate::lighting::Lighting;
+use crate::flashlight::Flashlight;
+use crate::hint::{Hint, HintConfig};
+use crate::lightmap::LightMap;
+use crate::maze::{Maze, find_char};
+use crate::pathfind::Pathfinder;
+use crate::render::{Scenery, render_frame};
+use crate::texture::TextureManager;
+use crate::{prepare_level, BLOCK_SIZE};
+
+const W: u32 = 256;
+const H: u32 = 192;
+const GOLDEN_DIR: &str = "tests/golden";
+const FAILURE_DIR: &str = "target/golden-failures";
+
+/// Diferencia máxima por canal que aún cuenta como el mismo color
+/// (distintos decodificadores JPEG no redondean igual).
+const CHANNEL_TOLERANCE: u8 = 8;
+/// Fracción de píxeles que puede salirse de la tolerancia.
+const MAX_BAD_FRACTION: f32 = 0.002;
+
+struct Pose { name: &'static str, x: f32, y: f32, a: f32 }
+
+impl Pose {
+    fn player(&self) -> Player {
+        Player::new(Vector2::new(self.x * BLOCK_SIZE as f32, self.y * BLOCK_SIZE as f32), self.a)
+    }
+}
+
+/// Pose con mirada vertical, altura de salto y agachado.
+struct View { pose: Pose, pitch: f32, z: f32, crouch: f32 }
+
+const MAZE1_POSES: &[Pose] = &[
+    Pose { name: "maze1_spawn_east",   x: 1.5,  y: 1.5, a: 0.0 },
+    Pose { name: "maze1_west_south",   x: 1.5,  y: 5.5, a: PI / 2.0 },
+    Pose { name: "maze1_donut_west",   x: 11.5, y: 5.5, a: PI },
+];
+
+const MAZE2_POSES: &[Pose] = &[
+    Pose { name: "maze2_spawn_angled", x: 1.5,  y: 1.5, a: 0.3 },
+    Pose { name: "maze2_donut_east",   x: 4.5,  y: 9.5, a: 0.0 },
+    Pose { name: "maze2_goal_south",   x: 11.5, y: 5.5, a: PI / 2.0 },
+];
+
+const HINT_POSES: &[Pose] = &[
+    Pose { name: "maze2_hint_trail", x: 1.5, y: 1.5, a: 0.0 },
+];
+
+const ENEMY_POSES: &[Pose] = &[
+    Pose { name: "maze5_gummy_east", x: 4.5, y: 3.5, a: 0.0 },
+];
+
+const EXIT_POSES: &[Pose] = &[
+    Pose { name: "maze2_exit_locked", x: 11.5, y: 9.5, a: PI / 2.0 },
+    Pose { name: "maze2_exit_open",   x: 11.5, y: 9.5, a: PI / 2.0 },
+];
+
+const DOOR_POSES: &[Pose] = &[
+    Pose { name: "maze4_door_half_open", x: 3.5, y: 7.5, a: 0.0 },
+    Pose { name: "maze4_door_closed",    x: 12.5, y: 7.5, a: PI },
+];
+
+/// `setup` ajusta la escena (luz, luces, linterna) antes de dibujar.
+fn render_pose(maze_file: &str, pose: &Pose, texman: &TextureManager, setup: &dyn Fn(&mut Scenery, &Maze)) -> OffscreenDisplay {
+    render_view(maze_file, &pose.player(), texman, setup)
+}
+
+fn render_view(maze_file: &str, player: &Player, texman: &TextureManager, setup: &dyn Fn(&mut Scenery, &Maze)) -> OffscreenDisplay {
+    let (maze, sprites, _) = prepare_level(maze_file, BLOCK_SIZE).expect("nivel de prueba inválido");
+
+    let mut framebuffer = Framebuffer::new(W, H);
+    let mut depth_buffer = vec![f32::INFINITY; W as usize];
+    let mut display = OffscreenDisplay::new();
+
+    // sin texturas de suelo, techo ni cielo se usan los colores lisos
+    let flat = Scenery::default();
+    let mut scenery = Scenery::new(flat.sky, flat.floor, Color::WHITE, &maze, texman);
+    setup(&mut scenery, &maze);
+
+    framebuffer.clear();
+    render_frame(&mut framebuffer, &maze, BLOCK_SIZE, player, &sprites, &mut depth_buffer, texman, &scenery);
+    display.present(&framebuffer, None, None);
+    display
+}
+
+fn read_ppm(path: &str) -> io::Result<(u32, u32, Vec<Color>)> {
+    let bad = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, msg));
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut fields = Vec::new();
+    while fields.len() < 4 {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 { return Err(bad("cabecera incompleta")); }
+        let line = line.split('#').next().unwrap_or("");
+        fields.extend(line.split_whitespace().map(str::to_owned));
+    }
+
+    if fields[0] != "P6" { return Err(bad("solo se admite P6")); }
+    let width: u32  = fields[1].parse().map_err(|_| bad("ancho inválido"))?;
+    let height: u32 = fields[2].parse().map_err(|_| bad("alto inválido"))?;
+    if fields[3] != "255" { return Err(bad("solo se admite maxval 255")); }
+
+    let mut raw = vec![0u8; (width * height * 3) as usize];
+    reader.read_exact(&mut raw)?;
+    let pixels = raw.chunks_exact(3).map(|p| Color::new(p[0], p[1], p[2], 255)).collect();
+    Ok((width, height, pixels))
+}
+
+/// Devuelve un mensaje de error si el frame no coincide con su referencia.
+fn compare_with_golden(name: &str, frame: &OffscreenDisplay) -> Option<String> {
+    let path = format!("{}/{}.ppm", GOLDEN_DIR, name);
+
+    if std::env::var_os("GOLDEN_UPDATE").is_some() {
+        std::fs::create_dir_all(GOLDEN_DIR).expect("no se pudo crear tests/golden");
+        frame.save_ppm(&path).expect("no se pudo escribir la referencia");
+        return None;
+    }
+
+    let (gw, gh, golden) = match read_ppm(&path) {
+        Ok(g) => g,
+        Err(e) => return Some(format!("{}: {} (GOLDEN_UPDATE=1 cargo test golden la genera)", name, e)),
+    };
+    if (gw, gh) != (frame.width, frame.height) {
+        return Some(format!("{}: tamaño {}x{}, se esperaba {}x{}", name, frame.width, frame.height, gw, gh));
+    }
+
+    let bad = golden.iter().zip(&frame.pixels).filter(|(g, a)| {
+        g.r.abs_diff(a.r) > CHANNEL_TOLERANCE
+            || g.g.abs_diff(a.g) > CHANNEL_TOLERANCE
+            || g.b.abs_diff(a.b) > CHANNEL_TOLERANCE
+    }).count();
+
+    let allowed = (golden.len() as f32 * MAX_BAD_FRACTION) as usize;
+    if bad <= allowed { return None; }
+
+    let actual = format!("{}/{}.ppm", FAILURE_DIR, name);
+    let _ = std::fs::create_dir_all(FAILURE_DIR);
+    let _ = write_ppm(&actual, frame.width, frame.height, &frame.pixels);
+    Some(format!("{}: {} píxeles distintos (máximo {}), frame actual en {}", name, bad, allowed, actual))
+}
+
+const SURFACE_POSES: &[Pose] = &[
+    Pose { name: "maze1_surfaces_angled", x: 1.5, y: 1.5, a: 0.4 },
+    Pose { name: "maze2_surfaces_goal",   x: 11.5, y: 5.5, a: PI / 2.0 },
+];
+
+const SKYBOX_POSES: &[Pose] = &[
+    Pose { name: "maze2_skybox_north", x: 1.5, y: 9.5,  a: -PI / 2.0 },
+    Pose { name: "maze2_skybox_wrap",  x: 4.5, y: 9.5,  a: 0.05 },
+];
+
+const DARK_POSES: &[Pose] = &[
+    Pose { name: "maze1_dark_corridor", x: 1.5, y: 1.5, a: 0.0 },
+];
+
+const LIGHT_POSES: &[Pose] = &[
+    Pose { name: "maze1_lights_corridor", x: 1.5, y: 1.5, a: 0.0 },
+    Pose { name: "maze1_lights_goal",     x: 10.5, y: 5.5, a: PI / 2.0 },
+];
+
+const VIEWS: &[View] = &[
+    View { pose: Pose { name: "maze2_look_up_sky",    x: 1.5, y: 9.5, a: -PI / 2.0 }, pitch: 0.45,  z: 0.0,  crouch: 0.0 },
+    View { pose: Pose { name: "maze1_look_down_crouch", x: 1.5, y: 1.5, a: 0.4 },   pitch: -0.35, z: 0.0,  crouch: 1.0 },
+    View { pose: Pose { name: "maze1_jump_donut",     x: 1.5, y: 1.5, a: 0.0 },     pitch: 0.0,   z: 18.0, crouch: 0.0 },
+];
+
+const FLASHLIGHT_POSES: &[Pose] = &[
+    Pose { name: "maze3_flashlight_full", x: 4.5, y: 9.5, a: 0.0 },
+    Pose { name: "maze3_flashlight_low",  x: 7.5, y: 9.5, a: PI },
+];
+
+fn check_poses(maze_file: &str, poses: &[Pose]) {
+    check_poses_with(maze_file, poses, &TextureManager::new_headless(), Lighting::default());
+}
+
+fn check_poses_with(maze_file: &str, poses: &[Pose], texman: &TextureManager, lighting: Lighting) {
+    check_poses_setup(maze_file, poses, texman, &|s, _| s.lighting = lighting);
+}
+
+fn check_poses_setup(maze_file: &str, poses: &[Pose], texman: &TextureManager, setup: &dyn Fn(&mut Scenery, &Maze)) {
+    let failures: Vec<String> = poses
+        .iter()
+        .filter_map(|pose| compare_with_golden(pose.name, &render_pose(maze_file, pose, texman, setup)))
+        .collect();
+
+    assert!(failures.is_empty(), "imágenes de referencia distintas:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn golden_maze1() {
+    check_poses("maze.txt", MAZE1_POSES);
+}
+
+#[test]
+fn golden_maze2() {
+    check_poses("maze2.txt", MAZE2_POSES);
+}
+
+#[test]
+fn golden_floor_and_ceiling() {
+    let manifest = Manifest::load(MANIFEST_FILE).expect("levels.toml inválido");
+    let mut texman = TextureManager::new_headless();
+    texman.use_wall_set(manifest.wall_set(&manifest.levels[0]));
+
+    check_poses_with("maze.txt", &SURFACE_POSES[..1], &texman, Lighting::default());
+    check_poses_with("maze2.txt", &SURFACE_POSES[1..], &texman, Lighting::default());
+}
+
+#[test]
+fn golden_skybox() {
+    let manifest = Manifest::load(MANIFEST_FILE).expect("levels.toml inválido");
+    let open_air = manifest.levels.iter().find(|l| l.sky_image.is_some()).expect("ningún nivel tiene sky_image");
+    let mut texman = TextureManager::new_headless();
+    texman.use_wall_set(manifest.wall_set(open_air));
+    texman.use_sky(open_air.sky_image.as_deref());
+
+    check_poses_with("maze2.txt", SKYBOX_POSES, &texman, Lighting::default());
+}
+
+#[test]
+fn golden_dark_lighting() {
+    let dark = Lighting::preset("dark").expect("falta el preset dark");
+    check_poses_with("maze.txt", DARK_POSES, &TextureManager::new_headless(), dark);
+}
+
+#[test]
+fn golden_point_lights() {
+    let manifest = Manifest::load(MANIFEST_FILE).expect("levels.toml inválido");
+    let (lights, ambient) = manifest.levels[0].point_lights().expect("el primer nivel no tiene luces");
+    let texman = TextureManager::new_headless();
+
+    let bake = |s: &mut Scenery, maze: &Maze| s.light_map = Some(LightMap::bake(maze, &lights, ambient, BLOCK_SIZE));
+    check_poses_setup("maze.txt", LIGHT_POSES, &texman, &bake);
+}
+
+#[test]
+fn golden_flashlight() {
+    let manifest = Manifest::load(MANIFEST_FILE).expect("levels.toml inválido");
+    let dark = manifest.levels.iter().find(|l| l.flashlight.is_some()).expect("ningún nivel usa linterna");
+    let lighting = dark.lighting();
+    let full = dark.flashlight(BLOCK_SIZE).expect("linterna inválida");
+    let mut low = full;
+    low.battery = low.capacity * 0.1;
+
+    // el parpadeo depende del tiempo: se fija el instante
+    let texman = TextureManager::new_headless();
+    let with = |torch: Flashlight| move |s: &mut Scenery, _: &Maze| {
+        s.lighting = lighting;
+        s.flashlight = Some(torch.beam(1.25));
+    };
+    check_poses_setup(&dark.maze, &FLASHLIGHT_POSES[..1], &texman, &with(full));
+    check_poses_setup(&dark.maze, &FLASHLIGHT_POSES[1..], &texman, &with(low));
+}
+
+#[test]
+fn golden_hint_trail() {
+    // estela desde la salida hasta la dona más cercana, como en `Playing::ask_hint`
+    let (_, sprites, _) = prepare_level("maze2.txt", BLOCK_SIZE).expect("nivel de prueba inválido");
+    let candies: Vec<_> = sprites
+        .iter()
+        .filter(|s| s.is_candy())
+        .map(|s| ((s.x as usize) / BLOCK_SIZE, (s.y as usize) / BLOCK_SIZE))
+        .collect();
+    let trail = |s: &mut Scenery, maze: &Maze| {
+        let mut hint = Hint::new(HintConfig::default());
+        hint.request(0.0, &Pathfinder::new(maze), (1, 1), candies.iter().copied()).expect("sin camino a ninguna dona");
+        s.breadcrumbs = hint.trail().cloned();
+    };
+    check_poses_setup("maze2.txt", HINT_POSES, &TextureManager::new_headless(), &trail);
+}
+
+#[test]
+fn golden_enemies() {
+    check_poses("maze5.txt", ENEMY_POSES);
+}
+
+#[test]
+fn golden_exit() {
+    let texman = TextureManager::new_headless();
+    let exit = |open: bool| move |s: &mut Scenery, maze: &Maze| s.exit = find_char(maze, 'g').map(|cell| (cell, open));
+    check_poses_setup("maze2.txt", &EXIT_POSES[..1], &texman, &exit(false));
+    check_poses_setup("maze2.txt", &EXIT_POSES[1..], &texman, &exit(true));
+}
+
+#[test]
+fn golden_doors() {
+    // la puerta roja a medio abrir y, desde el otro lado, cerrada
+    let half_open = |s: &mut Scenery, maze: &Maze| {
+        let mut doors = Doors::from_maze(maze);
+        doors.give_key('r');
+        let red = Vector2::new(6.5 * BLOCK_SIZE as f32, 7.5 * BLOCK_SIZE as f32);
+        doors.update(red, BLOCK_SIZE, 0.5);
+        s.doors = doors;
+    };
+    let texman = TextureManager::new_headless();
+    check_poses_setup("maze4.txt", &DOOR_POSES[..1], &texman, &half_open);
+    check_poses_setup("maze4.txt", &DOOR_POSES[1..], &texman, &|_, _| {});
+}
+
+#[test]
+fn golden_pitch_jump_and_crouch() {
+    let manifest = Manifest::load(MANIFEST_FILE).expect("levels.toml inválido");
+    let open_air = manifest.levels.iter().find(|l| l.sky_image.is_some()).expect("ningún nivel tiene sky_image");
+    let mut texman = TextureManager::new_headless();
+    texman.use_wall_set(manifest.wall_set(open_air));
+    texman.use_sky(open_air.sky_image.as_deref());
+
+    let failures: Vec<String> = VIEWS
+        .iter()
+        .filter_map(|view| {
+            let mut player = view.pose.player();
+            (player.pitch, player.z, player.crouch) = (view.pitch, view.z, view.crouch);
+            let maze = if view.pose.name.starts_with("maze2") { "maze2.txt" } else { "maze.txt" };
+            compare_with_golden(view.pose.name, &render_view(maze, &player, &texman, &|_, _| {}))
+        })
+        .collect();
+    assert!(failures.is_empty(), "imágenes de referencia distintas:\n{}", failures.join("\n"));
+}
@@ -0,0 +1,147 @@
+// hint.rs
+// Pista opcional: una estela de migas brillantes, en el suelo y en el
+// minimapa, por el camino más corto hasta el dulce sin recoger más cercano.
+// Cada uso quita segundos del reloj y después hay que esperar para pedir otra.
+use crate::pathfind::{Cell, Pathfinder};
+
+/// Ajustes de la pista de un nivel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HintConfig {
+    pub enabled: bool,
+    /// Segundos de espera entre un uso y el siguiente.
+    pub cooldown: f32,
+    /// Segundos que se restan al reloj en cada uso.
+    pub penalty: f32,
+    /// Segundos que se ve la estela.
+    pub duration: f32,
+}
+
+impl Default for HintConfig {
+    fn default() -> Self {
+        HintConfig { enabled: true, cooldown: 20.0, penalty: 5.0, duration: 8.0 }
+    }
+}
+
+/// Casillas de la estela, con búsqueda rápida para el render del suelo.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Breadcrumbs {
+    width: usize,
+    marked: Vec<bool>,
+    cells: Vec<Cell>,
+}
+
+impl Breadcrumbs {
+    pub fn new(path: &[Cell]) -> Breadcrumbs {
+        let width = path.iter().map(|c| c.0 + 1).max().unwrap_or(0);
+        let height = path.iter().map(|c| c.1 + 1).max().unwrap_or(0);
+        let mut marked = vec![false; width * height];
+        for &(i, j) in path { marked[j * width + i] = true; }
+        Breadcrumbs { width, marked, cells: path.to_vec() }
+    }
+
+    pub fn contains(&self, (i, j): Cell) -> bool {
+        i < self.width && self.marked.get(j * self.width + i).copied().unwrap_or(false)
+    }
+
+    /// Casillas en orden, desde el jugador hasta el dulce.
+    pub fn cells(&self) -> &[Cell] { &self.cells }
+}
+
+/// Por qué no se dio la pista.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Refusal {
+    Disabled,
+    /// Segundos que faltan para poder pedir otra.
+    Cooldown(f32),
+    /// No queda ningún dulce al que se pueda llegar.
+    NoTarget,
+}
+
+pub struct Hint {
+    config: HintConfig,
+    trail: Option<Breadcrumbs>,
+    visible_until: f64,
+    ready_at: f64,
+}
+
+impl Hint {
+    pub fn new(config: HintConfig) -> Hint {
+        Hint { config, trail: None, visible_until: 0.0, ready_at: 0.0 }
+    }
+
+    /// Marca el camino desde `from` hasta el más cercano de `targets`.
+    /// Devuelve los segundos que cuesta.
+    pub fn request(
+        &mut self,
+        now: f64,
+        paths: &Pathfinder,
+        from: Cell,
+        targets: impl IntoIterator<Item = Cell>,
+    ) -> Result<f32, Refusal> {
+        if !self.config.enabled { return Err(Refusal::Disabled); }
+        if now < self.ready_at { return Err(Refusal::Cooldown((self.ready_at - now) as f32)); }
+
+        // todas las distancias salen del campo guardado de `from`
+        let nearest = targets.into_iter().filter_map(|t| paths.distance(t, from).map(|d| (d, t))).min();
+        let path = nearest.and_then(|(_, to)| paths.shortest_path(from, to)).ok_or(Refusal::NoTarget)?;
+
+        self.trail = Some(Breadcrumbs::new(&path));
+        self.visible_until = now + self.config.duration as f64;
+        self.ready_at = now + self.config.cooldown as f64;
+        Ok(self.config.penalty)
+    }
+
+    /// Acorta la estela cuando el jugador avanza por ella y la apaga al
+    /// llegar o al acabarse el tiempo.
+    pub fn update(&mut self, now: f64, here: Cell) {
+        let Some(trail) = &self.trail else { return };
+        if now >= self.visible_until || trail.cells.last() == Some(&here) {
+            self.trail = None;
+        } else if let Some(k) = trail.cells.iter().position(|&c| c == here).filter(|&k| k > 0) {
+            self.trail = Some(Breadcrumbs::new(&trail.cells[k..]));
+        }
+    }
+
+    pub fn trail(&self) -> Option<&Breadcrumbs> { self.trail.as_ref() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::Maze;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|r| r.chars().collect()).collect()
+    }
+
+    #[test]
+    fn leads_to_the_nearest_candy_and_waits_between_uses() {
+        let m = maze(&[
+            "+-------+",
+            "|       |",
+            "+-+-+-+ |",
+            "|       |",
+            "+-------+",
+        ]);
+        let paths = Pathfinder::new(&m);
+        let mut hint = Hint::new(HintConfig::default());
+
+        // (1, 3) está más cerca en línea recta, pero por el pasillo queda lejos
+        let penalty = hint.request(0.0, &paths, (1, 1), [(1, 3), (5, 1)]).unwrap();
+        assert_eq!(penalty, HintConfig::default().penalty);
+        let trail = hint.trail().unwrap();
+        assert_eq!((trail.cells()[0], *trail.cells().last().unwrap()), ((1, 1), (5, 1)));
+        assert!(trail.contains((3, 1)) && !trail.contains((1, 3)));
+
+        assert!(matches!(hint.request(1.0, &paths, (1, 1), [(5, 1)]), Err(Refusal::Cooldown(_))));
+
+        // al avanzar se borra lo recorrido y al llegar se apaga
+        hint.update(1.0, (3, 1));
+        assert_eq!(hint.trail().unwrap().cells()[0], (3, 1));
+        hint.update(2.0, (5, 1));
+        assert!(hint.trail().is_none());
+
+        let off = HintConfig { enabled: false, ..HintConfig::default() };
+        assert_eq!(Hint::new(off).request(0.0, &paths, (1, 1), [(5, 1)]), Err(Refusal::Disabled));
+    }
+}
@@ -0,0 +1,388 @@
+// input.rs
+// Capa de acciones: el juego pregunta "¿está activa la acción X?" y esta capa
+// la traduce a teclas, botones del mouse, botones o sticks del gamepad según
+// `[input]` en settings.toml. Lo que no esté configurado usa DEFAULT_BINDINGS.
+use raylib::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+use KeyboardKey as K;
+
+/// Lo que el jugador puede pedir, sin importar con qué dispositivo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    LookUp,
+    LookDown,
+    Jump,
+    Crouch,
+    Confirm,
+    Back,
+    Pause,
+    Map,
+    Hint,
+}
+
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::MoveForward, Action::MoveBack, Action::StrafeLeft, Action::StrafeRight,
+        Action::TurnLeft, Action::TurnRight, Action::LookUp, Action::LookDown,
+        Action::Jump, Action::Crouch, Action::Confirm, Action::Back, Action::Pause, Action::Map,
+        Action::Hint,
+    ];
+}
+
+/// Una entrada física. En settings.toml se escribe como texto: "W", "SPACE",
+/// "mouse:LEFT", "pad:RIGHT_FACE_DOWN" o "axis:LEFT_Y-" (mitad negativa del eje).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Binding {
+    Key(KeyboardKey),
+    Mouse(MouseButton),
+    Pad(GamepadButton),
+    Axis { axis: GamepadAxis, positive: bool },
+}
+
+const KEY_NAMES: &[(&str, KeyboardKey)] = &[
+    ("A", K::KEY_A), ("B", K::KEY_B), ("C", K::KEY_C), ("D", K::KEY_D), ("E", K::KEY_E),
+    ("F", K::KEY_F), ("G", K::KEY_G), ("H", K::KEY_H), ("I", K::KEY_I), ("J", K::KEY_J),
+    ("K", K::KEY_K), ("L", K::KEY_L), ("M", K::KEY_M), ("N", K::KEY_N), ("O", K::KEY_O),
+    ("P", K::KEY_P), ("Q", K::KEY_Q), ("R", K::KEY_R), ("S", K::KEY_S), ("T", K::KEY_T),
+    ("U", K::KEY_U), ("V", K::KEY_V), ("W", K::KEY_W), ("X", K::KEY_X), ("Y", K::KEY_Y),
+    ("Z", K::KEY_Z), ("0", K::KEY_ZERO), ("1", K::KEY_ONE), ("2", K::KEY_TWO), ("3", K::KEY_THREE),
+    ("4", K::KEY_FOUR), ("5", K::KEY_FIVE), ("6", K::KEY_SIX), ("7", K::KEY_SEVEN),
+    ("8", K::KEY_EIGHT), ("9", K::KEY_NINE), ("SPACE", K::KEY_SPACE), ("ESCAPE", K::KEY_ESCAPE),
+    ("ENTER", K::KEY_ENTER), ("TAB", K::KEY_TAB), ("BACKSPACE", K::KEY_BACKSPACE),
+    ("INSERT", K::KEY_INSERT), ("DELETE", K::KEY_DELETE), ("RIGHT", K::KEY_RIGHT),
+    ("LEFT", K::KEY_LEFT), ("DOWN", K::KEY_DOWN), ("UP", K::KEY_UP), ("PAGE_UP", K::KEY_PAGE_UP),
+    ("PAGE_DOWN", K::KEY_PAGE_DOWN), ("HOME", K::KEY_HOME), ("END", K::KEY_END),
+    ("LEFT_SHIFT", K::KEY_LEFT_SHIFT), ("LEFT_CONTROL", K::KEY_LEFT_CONTROL),
+    ("LEFT_ALT", K::KEY_LEFT_ALT), ("RIGHT_SHIFT", K::KEY_RIGHT_SHIFT),
+    ("RIGHT_CONTROL", K::KEY_RIGHT_CONTROL), ("RIGHT_ALT", K::KEY_RIGHT_ALT), ("F1", K::KEY_F1),
+    ("F2", K::KEY_F2), ("F3", K::KEY_F3), ("F4", K::KEY_F4), ("F5", K::KEY_F5), ("F6", K::KEY_F6),
+    ("F7", K::KEY_F7), ("F8", K::KEY_F8), ("F9", K::KEY_F9), ("F10", K::KEY_F10),
+    ("F11", K::KEY_F11), ("F12", K::KEY_F12),
+];
+
+const MOUSE_NAMES: &[(&str, MouseButton)] = &[
+    ("LEFT", MouseButton::MOUSE_BUTTON_LEFT),
+    ("RIGHT", MouseButton::MOUSE_BUTTON_RIGHT),
+    ("MIDDLE", MouseButton::MOUSE_BUTTON_MIDDLE),
+];
+
+const PAD_NAMES: &[(&str, GamepadButton)] = &[
+    ("LEFT_FACE_UP", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP),
+    ("LEFT_FACE_RIGHT", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT),
+    ("LEFT_FACE_DOWN", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN),
+    ("LEFT_FACE_LEFT", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT),
+    ("RIGHT_FACE_UP", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP),
+    ("RIGHT_FACE_RIGHT", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT),
+    ("RIGHT_FACE_DOWN", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+    ("RIGHT_FACE_LEFT", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT),
+    ("LEFT_TRIGGER_1", GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1),
+    ("LEFT_TRIGGER_2", GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2),
+    ("RIGHT_TRIGGER_1", GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1),
+    ("RIGHT_TRIGGER_2", GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2),
+    ("SELECT", GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT),
+    ("START", GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT),
+    ("LEFT_THUMB", GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB),
+    ("RIGHT_THUMB", GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB),
+];
+
+const AXIS_NAMES: &[(&str, GamepadAxis)] = &[
+    ("LEFT_X", GamepadAxis::GAMEPAD_AXIS_LEFT_X),
+    ("LEFT_Y", GamepadAxis::GAMEPAD_AXIS_LEFT_Y),
+    ("RIGHT_X", GamepadAxis::GAMEPAD_AXIS_RIGHT_X),
+    ("RIGHT_Y", GamepadAxis::GAMEPAD_AXIS_RIGHT_Y),
+    ("LEFT_TRIGGER", GamepadAxis::GAMEPAD_AXIS_LEFT_TRIGGER),
+    ("RIGHT_TRIGGER", GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingError(String);
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "entrada desconocida \"{}\"", self.0)
+    }
+}
+
+fn lookup<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+    table.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|&(_, v)| v)
+}
+
+impl TryFrom<String> for Binding {
+    type Error = BindingError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let found = match s.split_once(':') {
+            None => lookup(KEY_NAMES, &s).map(Binding::Key),
+            Some(("mouse", name)) => lookup(MOUSE_NAMES, name).map(Binding::Mouse),
+            Some(("pad", name)) => lookup(PAD_NAMES, name).map(Binding::Pad),
+            Some(("axis", name)) => {
+                let (name, positive) = match name.strip_suffix('-') {
+                    Some(n) => (n, false),
+                    None => (name.strip_suffix('+').unwrap_or(name), true),
+                };
+                lookup(AXIS_NAMES, name).map(|axis| Binding::Axis { axis, positive })
+            }
+            Some(_) => None,
+        };
+        found.ok_or(BindingError(s))
+    }
+}
+
+/// Asignación de fábrica de cada acción.
+fn default_bindings(action: Action) -> Vec<Binding> {
+    use Binding::{Axis, Key, Mouse, Pad};
+    use GamepadAxis as A;
+    use GamepadButton as P;
+    let axis = |axis, positive| Axis { axis, positive };
+    match action {
+        Action::MoveForward => vec![Key(K::KEY_W), Key(K::KEY_UP), Pad(P::GAMEPAD_BUTTON_LEFT_FACE_UP), axis(A::GAMEPAD_AXIS_LEFT_Y, false)],
+        Action::MoveBack => vec![Key(K::KEY_S), Key(K::KEY_DOWN), Pad(P::GAMEPAD_BUTTON_LEFT_FACE_DOWN), axis(A::GAMEPAD_AXIS_LEFT_Y, true)],
+        Action::StrafeLeft => vec![Key(K::KEY_A), axis(A::GAMEPAD_AXIS_LEFT_X, false)],
+        Action::StrafeRight => vec![Key(K::KEY_D), axis(A::GAMEPAD_AXIS_LEFT_X, true)],
+        Action::TurnLeft => vec![Key(K::KEY_LEFT), axis(A::GAMEPAD_AXIS_RIGHT_X, false)],
+        Action::TurnRight => vec![Key(K::KEY_RIGHT), axis(A::GAMEPAD_AXIS_RIGHT_X, true)],
+        Action::LookUp => vec![Key(K::KEY_PAGE_UP), axis(A::GAMEPAD_AXIS_RIGHT_Y, false)],
+        Action::LookDown => vec![Key(K::KEY_PAGE_DOWN), axis(A::GAMEPAD_AXIS_RIGHT_Y, true)],
+        Action::Jump => vec![Key(K::KEY_SPACE), Mouse(MouseButton::MOUSE_BUTTON_RIGHT), Pad(P::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)],
+        Action::Crouch => vec![Key(K::KEY_LEFT_CONTROL), Key(K::KEY_C), Pad(P::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT)],
+        Action::Confirm => vec![Key(K::KEY_ENTER), Key(K::KEY_SPACE), Pad(P::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)],
+        Action::Back => vec![Key(K::KEY_BACKSPACE), Pad(P::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT)],
+        Action::Pause => vec![Key(K::KEY_P), Key(K::KEY_ESCAPE), Pad(P::GAMEPAD_BUTTON_MIDDLE_RIGHT)],
+        Action::Map => vec![Key(K::KEY_M), Key(K::KEY_TAB), Pad(P::GAMEPAD_BUTTON_MIDDLE_LEFT)],
+        Action::Hint => vec![Key(K::KEY_H), Pad(P::GAMEPAD_BUTTON_RIGHT_FACE_UP)],
+    }
+}
+
+/// Tabla `[input]` de settings.toml.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct InputConfig {
+    /// Radianes por píxel de mouse.
+    pub mouse_sensitivity: f32,
+    pub invert_mouse_y: bool,
+    /// Invierte el stick derecho en vertical (mirar arriba y abajo).
+    pub invert_stick_y: bool,
+    /// Parte del recorrido de los sticks que se ignora, de 0 a 1.
+    pub deadzone: f32,
+    /// Número del gamepad que se usa.
+    pub gamepad: i32,
+    /// Solo hace falta poner las acciones que se cambian.
+    pub bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        InputConfig {
+            mouse_sensitivity: 0.003,
+            invert_mouse_y: false,
+            invert_stick_y: false,
+            deadzone: 0.2,
+            gamepad: 0,
+            bindings: HashMap::new(),
+        }
+    }
+}
+
+/// De dónde se leen las entradas; raylib en el juego, un doble en las pruebas.
+pub trait InputSource {
+    fn key_down(&self, key: KeyboardKey) -> bool;
+    fn mouse_down(&self, button: MouseButton) -> bool;
+    fn mouse_delta(&self) -> Vector2;
+    /// `None` si no hay ese gamepad conectado.
+    fn pad_down(&self, pad: i32, button: GamepadButton) -> Option<bool>;
+    fn pad_axis(&self, pad: i32, axis: GamepadAxis) -> Option<f32>;
+}
+
+impl InputSource for RaylibHandle {
+    fn key_down(&self, key: KeyboardKey) -> bool { self.is_key_down(key) }
+    fn mouse_down(&self, button: MouseButton) -> bool { self.is_mouse_button_down(button) }
+    fn mouse_delta(&self) -> Vector2 { self.get_mouse_delta() }
+    fn pad_down(&self, pad: i32, button: GamepadButton) -> Option<bool> {
+        self.is_gamepad_available(pad).then(|| self.is_gamepad_button_down(pad, button))
+    }
+    fn pad_axis(&self, pad: i32, axis: GamepadAxis) -> Option<f32> {
+        self.is_gamepad_available(pad).then(|| self.get_gamepad_axis_movement(pad, axis))
+    }
+}
+
+/// Valor a partir del cual una acción analógica cuenta como pulsada.
+const PRESS_THRESHOLD: f32 = 0.5;
+
+const DIGIT_KEYS: [KeyboardKey; 9] = [
+    K::KEY_ONE, K::KEY_TWO, K::KEY_THREE, K::KEY_FOUR, K::KEY_FIVE,
+    K::KEY_SIX, K::KEY_SEVEN, K::KEY_EIGHT, K::KEY_NINE,
+];
+
+/// Estado de las acciones en el frame actual y en el anterior.
+pub struct Input {
+    config: InputConfig,
+    bindings: Vec<Vec<Binding>>,
+    values: [f32; Action::ALL.len()],
+    previous: [f32; Action::ALL.len()],
+    digits: [bool; 9],
+    previous_digits: [bool; 9],
+    look: Vector2,
+}
+
+impl Input {
+    pub fn new(config: InputConfig) -> Input {
+        let bindings = Action::ALL
+            .iter()
+            .map(|a| config.bindings.get(a).cloned().unwrap_or_else(|| default_bindings(*a)))
+            .collect();
+        Input {
+            config,
+            bindings,
+            values: [0.0; Action::ALL.len()],
+            previous: [0.0; Action::ALL.len()],
+            digits: [false; 9],
+            previous_digits: [false; 9],
+            look: Vector2::zero(),
+        }
+    }
+
+    /// Lee todas las entradas; se llama una vez por frame, antes de preguntar.
+    pub fn update(&mut self, source: &dyn InputSource) {
+        self.previous = self.values;
+        for (k, bindings) in self.bindings.iter().enumerate() {
+            self.values[k] = bindings.iter().map(|b| self.read(source, *b)).fold(0.0, f32::max);
+        }
+
+        self.previous_digits = self.digits;
+        self.digits = DIGIT_KEYS.map(|key| source.key_down(key));
+
+        let md = source.mouse_delta();
+        let sign_y = if self.config.invert_mouse_y { -1.0 } else { 1.0 };
+        self.look = Vector2::new(md.x, md.y * sign_y) * self.config.mouse_sensitivity;
+    }
+
+    pub fn set_invert_mouse_y(&mut self, invert: bool) {
+        self.config.invert_mouse_y = invert;
+    }
+
+    /// Valor de 0 a 1 de una entrada; los ejes pasan por la zona muerta.
+    fn read(&self, source: &dyn InputSource, binding: Binding) -> f32 {
+        let pad = self.config.gamepad;
+        match binding {
+            Binding::Key(key) => source.key_down(key) as u8 as f32,
+            Binding::Mouse(button) => source.mouse_down(button) as u8 as f32,
+            Binding::Pad(button) => source.pad_down(pad, button).unwrap_or(false) as u8 as f32,
+            Binding::Axis { axis, positive } => {
+                let Some(mut v) = source.pad_axis(pad, axis) else { return 0.0 };
+                if self.config.invert_stick_y && axis == GamepadAxis::GAMEPAD_AXIS_RIGHT_Y { v = -v; }
+                if !positive { v = -v; }
+                let dz = self.config.deadzone.clamp(0.0, 0.95);
+                ((v - dz) / (1.0 - dz)).clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    fn index(action: Action) -> usize {
+        Action::ALL.iter().position(|a| *a == action).unwrap_or(0)
+    }
+
+    /// Intensidad de la acción, de 0 a 1 (los sticks dan valores intermedios).
+    pub fn value(&self, action: Action) -> f32 {
+        self.values[Input::index(action)]
+    }
+
+    pub fn active(&self, action: Action) -> bool {
+        self.value(action) >= PRESS_THRESHOLD
+    }
+
+    /// Si la acción se activó en este frame.
+    pub fn pressed(&self, action: Action) -> bool {
+        let k = Input::index(action);
+        self.values[k] >= PRESS_THRESHOLD && self.previous[k] < PRESS_THRESHOLD
+    }
+
+    /// `value(positive) - value(negative)`, para ejes como avanzar y retroceder.
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        self.value(positive) - self.value(negative)
+    }
+
+    /// Giro del mouse en este frame, en radianes, ya con sensibilidad e inversión.
+    pub fn mouse_look(&self) -> Vector2 {
+        self.look
+    }
+
+    /// Tecla numérica (1 a 9) pulsada en este frame, como índice desde 0.
+    pub fn digit_pressed(&self) -> Option<usize> {
+        (0..9).find(|&k| self.digits[k] && !self.previous_digits[k])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Fake { keys: Vec<KeyboardKey>, left_y: Option<f32>, right_y: Option<f32> }
+
+    impl InputSource for Fake {
+        fn key_down(&self, key: KeyboardKey) -> bool { self.keys.contains(&key) }
+        fn mouse_down(&self, _: MouseButton) -> bool { false }
+        fn mouse_delta(&self) -> Vector2 { Vector2::new(10.0, -4.0) }
+        fn pad_down(&self, _: i32, _: GamepadButton) -> Option<bool> { self.left_y.map(|_| false) }
+        fn pad_axis(&self, _: i32, axis: GamepadAxis) -> Option<f32> {
+            match axis {
+                GamepadAxis::GAMEPAD_AXIS_LEFT_Y => self.left_y,
+                GamepadAxis::GAMEPAD_AXIS_RIGHT_Y => self.right_y,
+                _ => self.left_y.map(|_| 0.0),
+            }
+        }
+    }
+
+    #[test]
+    fn bindings_parse_from_text() {
+        let b = |s: &str| Binding::try_from(s.to_string());
+        assert_eq!(b("w"), Ok(Binding::Key(K::KEY_W)));
+        assert_eq!(b("LEFT_CONTROL"), Ok(Binding::Key(K::KEY_LEFT_CONTROL)));
+        assert_eq!(b("mouse:RIGHT"), Ok(Binding::Mouse(MouseButton::MOUSE_BUTTON_RIGHT)));
+        assert_eq!(b("pad:START"), Ok(Binding::Pad(GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT)));
+        assert_eq!(b("axis:LEFT_Y-"), Ok(Binding::Axis { axis: GamepadAxis::GAMEPAD_AXIS_LEFT_Y, positive: false }));
+        assert!(b("pad:TURBO").is_err() && b("joystick:A").is_err());
+
+        let config: InputConfig = toml::from_str("deadzone = 0.1\n[bindings]\njump = [\"J\"]\n").unwrap();
+        let mut input = Input::new(config);
+        input.update(&Fake { keys: vec![K::KEY_J, K::KEY_SPACE], ..Fake::default() });
+        assert!(input.active(Action::Jump));
+        input.update(&Fake { keys: vec![K::KEY_SPACE], ..Fake::default() });
+        assert!(!input.active(Action::Jump));
+    }
+
+    #[test]
+    fn sticks_use_deadzone_invert_and_press_edges() {
+        let mut input = Input::new(InputConfig { invert_stick_y: true, invert_mouse_y: true, ..InputConfig::default() });
+
+        input.update(&Fake { left_y: Some(-0.1), right_y: Some(0.0), ..Fake::default() });
+        assert_eq!(input.value(Action::MoveForward), 0.0);
+
+        input.update(&Fake { left_y: Some(-0.6), right_y: Some(0.6), ..Fake::default() });
+        assert!((input.value(Action::MoveForward) - 0.5).abs() < 1e-5);
+        assert!(input.pressed(Action::MoveForward));
+        // stick derecho invertido: hacia abajo mira arriba
+        assert!(input.value(Action::LookUp) > 0.0 && input.value(Action::LookDown) == 0.0);
+        assert!(input.mouse_look().y > 0.0);
+
+        input.update(&Fake { left_y: Some(-1.0), right_y: Some(0.0), ..Fake::default() });
+        assert!(input.active(Action::MoveForward) && !input.pressed(Action::MoveForward));
+        assert_eq!(input.axis(Action::MoveBack, Action::MoveForward), 1.0);
+
+        input.update(&Fake { keys: vec![K::KEY_THREE], ..Fake::default() });
+        assert_eq!(input.digit_pressed(), Some(2));
+        input.update(&Fake { keys: vec![K::KEY_THREE], ..Fake::default() });
+        assert_eq!(input.digit_pressed(), None);
+    }
+}
@@ -0,0 +1,378 @@
+// levels.rs
+use raylib::prelude::Color;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use crate::enemy::EnemyConfig;
+use crate::hint::HintConfig;
+use crate::flashlight::Flashlight;
+use crate::lighting::{Lighting, PRESETS};
+use crate::lightmap::PointLight;
+use crate::texture::{CEILING, FLOOR, FLOOR_ALT};
+
+pub const MANIFEST_FILE: &str = "levels.toml";
+
+/// Lista de niveles y conjuntos de texturas leída de `levels.toml`.
+#[derive(Debug, Default, Deserialize)]
+pub struct Manifest {
+    #[serde(rename = "level", default)]
+    pub levels: Vec<LevelSpec>,
+    #[serde(default)]
+    pub wall_sets: HashMap<String, WallSet>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelSpec {
+    pub name: String,
+    pub maze: String,
+    pub time_limit: i32,
+    /// Dulces necesarios para ganar; si falta, hay que recogerlos todos.
+    pub candies_required: Option<u32>,
+    /// Qué hay que hacer para ganar; si falta, solo recoger los dulces.
+    #[serde(default)]
+    pub objective: Objective,
+    pub walls: String,
+    pub sky: HexColor,
+    pub floor: HexColor,
+    /// Panorama del cielo; sin imagen se usa `sky` liso.
+    pub sky_image: Option<String>,
+    /// Color que multiplica el panorama (blanco si falta).
+    pub sky_tint: Option<HexColor>,
+    pub music: String,
+    #[serde(default)]
+    pub lighting: LightingSpec,
+    #[serde(default, rename = "light")]
+    pub lights: Vec<LightSpec>,
+    /// Si está, el nivel se juega a oscuras con linterna.
+    pub flashlight: Option<FlashlightSpec>,
+    /// Ajustes de los ositos 'E' del laberinto.
+    #[serde(default)]
+    pub enemies: EnemySpec,
+    /// Costo y espera de la pista, o `enabled = false` para quitarla.
+    #[serde(default)]
+    pub hint: HintSpec,
+}
+
+impl LevelSpec {
+    pub fn candies_needed(&self, total: u32) -> u32 {
+        self.candies_required.map_or(total, |n| n.min(total))
+    }
+
+    /// Luz del nivel: el preset (o "day") con los valores sueltos encima.
+    pub fn lighting(&self) -> Lighting {
+        let spec = &self.lighting;
+        let mut l = spec.preset.as_deref().and_then(Lighting::preset).unwrap_or_default();
+        if let Some(v) = spec.falloff { l.falloff = v.max(0.0); }
+        if let Some(v) = spec.min_light { l.min_light = v.clamp(0.0, 1.0); }
+        if let Some(v) = spec.side_shade { l.side_shade = v.clamp(0.0, 1.0); }
+        if let Some(v) = spec.fog_color { l.fog_color = v.0; }
+        if let Some(v) = spec.fog_density { l.fog_density = v.max(0.0); }
+        l
+    }
+
+    /// Luces del nivel, o `None` si no tiene ni luces ni luz ambiente propia.
+    pub fn point_lights(&self) -> Option<(Vec<PointLight>, f32)> {
+        if self.lights.is_empty() && self.lighting.ambient.is_none() { return None; }
+        let lights = self.lights.iter().map(LightSpec::to_light).collect();
+        Some((lights, self.lighting.ambient.unwrap_or(1.0).max(0.0)))
+    }
+
+    /// Linterna del nivel con la pila llena, o `None` si el nivel no la usa.
+    pub fn flashlight(&self, block_size: usize) -> Option<Flashlight> {
+        let spec = self.flashlight.as_ref()?;
+        let mut f = Flashlight::new(block_size);
+        if let Some(v) = spec.cone { f.half_angle = (v.clamp(1.0, 170.0) * 0.5).to_radians(); }
+        if let Some(v) = spec.range { f.range = v.max(0.0) * block_size as f32; }
+        if let Some(v) = spec.ambient { f.ambient = v.clamp(0.0, 1.0); }
+        if let Some(v) = spec.flicker { f.flicker = v.clamp(0.0, 1.0); }
+        if let Some(v) = spec.battery { f.capacity = v.max(0.0); }
+        if let Some(v) = spec.recharge { f.recharge = v.max(0.0); }
+        f.battery = f.capacity;
+        Some(f)
+    }
+
+    /// Comportamiento de los enemigos del nivel.
+    pub fn enemy_config(&self, block_size: usize) -> EnemyConfig {
+        let spec = &self.enemies;
+        let bs = block_size as f32;
+        let mut e = EnemyConfig::new(block_size);
+        if let Some(v) = spec.speed { e.speed = v.max(0.0) * bs; }
+        if let Some(v) = spec.chase_speed { e.chase_speed = v.max(0.0) * bs; }
+        if let Some(v) = spec.sight { e.sight = v.max(0.0) * bs; }
+        if let Some(v) = spec.penalty { e.penalty = v.max(0.0); }
+        e
+    }
+
+    /// Pista del nivel.
+    pub fn hint_config(&self) -> HintConfig {
+        let spec = &self.hint;
+        let mut h = HintConfig::default();
+        if let Some(v) = spec.enabled { h.enabled = v; }
+        if let Some(v) = spec.cooldown { h.cooldown = v.max(0.0); }
+        if let Some(v) = spec.penalty { h.penalty = v.max(0.0); }
+        if let Some(v) = spec.duration { h.duration = v.max(0.0); }
+        h
+    }
+}
+
+/// Qué hay que hacer para ganar un nivel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Objective {
+    /// Recoger los dulces (`candies_required` o todos).
+    #[default]
+    Collect,
+    /// Recoger los dulces y después llegar a la salida 'g'.
+    CollectThenExit,
+    /// Llegar a la salida; los dulces no hacen falta.
+    ReachExit,
+}
+
+impl Objective {
+    pub fn needs_exit(self) -> bool { self != Objective::Collect }
+
+    /// Si el laberinto tiene que traer dulces.
+    pub fn needs_collectibles(self) -> bool { self != Objective::ReachExit }
+
+    /// Si la salida deja pasar con `collected` de `needed` dulces.
+    pub fn exit_open(self, collected: u32, needed: u32) -> bool {
+        match self {
+            Objective::Collect => false,
+            Objective::CollectThenExit => collected >= needed,
+            Objective::ReachExit => true,
+        }
+    }
+
+    /// Si el nivel queda superado; `on_exit` es si el jugador pisa la salida.
+    pub fn complete(self, collected: u32, needed: u32, on_exit: bool) -> bool {
+        match self {
+            Objective::Collect => needed > 0 && collected >= needed,
+            _ => on_exit && self.exit_open(collected, needed),
+        }
+    }
+}
+
+/// Tabla `[level.enemies]`; lo que falte toma el valor de `EnemyConfig::new`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EnemySpec {
+    /// Casillas por segundo patrullando y persiguiendo.
+    pub speed: Option<f32>,
+    pub chase_speed: Option<f32>,
+    /// Alcance de la vista, en casillas.
+    pub sight: Option<f32>,
+    /// Segundos que cuesta que te alcancen.
+    pub penalty: Option<f32>,
+}
+
+/// Tabla `[level.hint]`; lo que falte toma el valor de `HintConfig::default`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HintSpec {
+    pub enabled: Option<bool>,
+    /// Segundos entre usos.
+    pub cooldown: Option<f32>,
+    /// Segundos que se restan al reloj por uso.
+    pub penalty: Option<f32>,
+    /// Segundos que se ve la estela.
+    pub duration: Option<f32>,
+}
+
+/// Tabla `[level.flashlight]`; lo que falte toma el valor de `Flashlight::new`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FlashlightSpec {
+    /// Apertura total del cono, en grados.
+    pub cone: Option<f32>,
+    /// Alcance en casillas.
+    pub range: Option<f32>,
+    pub ambient: Option<f32>,
+    pub flicker: Option<f32>,
+    /// Segundos de pila llena.
+    pub battery: Option<f32>,
+    /// Segundos que devuelve cada pila 'B'.
+    pub recharge: Option<f32>,
+}
+
+/// Tabla `[[level.light]]`: una luz en la casilla `line`, `column` del
+/// archivo del laberinto (desde 1, como en los mensajes de error).
+#[derive(Debug, Clone, Deserialize)]
+pub struct LightSpec {
+    pub line: usize,
+    pub column: usize,
+    pub color: HexColor,
+    /// Alcance en casillas.
+    pub radius: f32,
+    #[serde(default = "default_intensity")]
+    pub intensity: f32,
+}
+
+fn default_intensity() -> f32 { 1.0 }
+
+impl LightSpec {
+    fn to_light(&self) -> PointLight {
+        PointLight {
+            cell: (self.column.saturating_sub(1), self.line.saturating_sub(1)),
+            color: self.color.0,
+            radius: self.radius.max(0.0),
+            intensity: self.intensity.max(0.0),
+        }
+    }
+}
+
+/// Tabla `[level.lighting]`; todo es opcional.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LightingSpec {
+    pub preset: Option<String>,
+    pub falloff: Option<f32>,
+    pub min_light: Option<f32>,
+    pub side_shade: Option<f32>,
+    pub fog_color: Option<HexColor>,
+    pub fog_density: Option<f32>,
+    /// Luz base de las casillas cuando el nivel tiene luces (1 = sin cambio).
+    pub ambient: Option<f32>,
+}
+
+/// Imágenes para cada tipo de pared del laberinto.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WallSet {
+    pub corner: String,
+    pub horizontal: String,
+    pub vertical: String,
+    pub goal: String,
+    pub border: String,
+    /// Suelo y techo; si faltan se usan los colores lisos del nivel.
+    pub floor: Option<String>,
+    pub floor_alt: Option<String>,
+    pub ceiling: Option<String>,
+}
+
+impl WallSet {
+    /// Casilla del laberinto y la imagen que le corresponde.
+    pub fn entries(&self) -> [(char, &str); 5] {
+        [
+            ('+', self.corner.as_str()),
+            ('-', self.horizontal.as_str()),
+            ('|', self.vertical.as_str()),
+            ('g', self.goal.as_str()),
+            ('#', self.border.as_str()),
+        ]
+    }
+
+    /// Claves de suelo y techo con su imagen, si el conjunto la tiene.
+    pub fn surfaces(&self) -> [(char, Option<&str>); 3] {
+        [
+            (FLOOR, self.floor.as_deref()),
+            (FLOOR_ALT, self.floor_alt.as_deref()),
+            (CEILING, self.ceiling.as_deref()),
+        ]
+    }
+}
+
+/// Color escrito como "#RRGGBB" en el manifiesto.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct HexColor(pub Color);
+
+impl TryFrom<String> for HexColor {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let hex = s.strip_prefix('#').unwrap_or(&s);
+        let v = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6);
+        match v {
+            Some(v) => Ok(HexColor(Color::new((v >> 16) as u8, (v >> 8) as u8, v as u8, 255))),
+            None => Err(format!("color inválido \"{}\", se espera #RRGGBB", s)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    Io { path: String, source: std::io::Error },
+    Parse { path: String, source: toml::de::Error },
+    NoLevels,
+    UnknownWallSet { level: String, walls: String },
+    UnknownLightingPreset { level: String, preset: String },
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Io { path, source } =>
+                write!(f, "no se pudo leer {}: {}", path, source),
+            ManifestError::Parse { path, source } =>
+                write!(f, "{}: {}", path, source.message().trim()),
+            ManifestError::NoLevels =>
+                write!(f, "el manifiesto no tiene ningún [[level]]"),
+            ManifestError::UnknownWallSet { level, walls } =>
+                write!(f, "nivel \"{}\": no existe el conjunto de paredes \"{}\"", level, walls),
+            ManifestError::UnknownLightingPreset { level, preset } =>
+                write!(f, "nivel \"{}\": no existe la luz \"{}\" (hay {})", level, preset, PRESETS.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+impl Manifest {
+    pub fn load(path: &str) -> Result<Manifest, ManifestError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|source| ManifestError::Io { path: path.to_string(), source })?;
+        let manifest: Manifest = toml::from_str(&text)
+            .map_err(|source| ManifestError::Parse { path: path.to_string(), source })?;
+
+        if manifest.levels.is_empty() { return Err(ManifestError::NoLevels); }
+        for level in &manifest.levels {
+            if !manifest.wall_sets.contains_key(&level.walls) {
+                return Err(ManifestError::UnknownWallSet {
+                    level: level.name.clone(),
+                    walls: level.walls.clone(),
+                });
+            }
+            if let Some(preset) = &level.lighting.preset && Lighting::preset(preset).is_none() {
+                return Err(ManifestError::UnknownLightingPreset {
+                    level: level.name.clone(),
+                    preset: preset.clone(),
+                });
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Si el laberinto `maze` tiene que traer dulces según el objetivo de sus
+    /// niveles. Un laberinto que no está en el manifiesto, siempre.
+    pub fn needs_collectibles(&self, maze: &str) -> bool {
+        let path = |p: &str| Path::new(p).components().filter(|c| *c != Component::CurDir).collect::<PathBuf>();
+        let mut specs = self.levels.iter().filter(|l| path(&l.maze) == path(maze)).peekable();
+        specs.peek().is_none() || specs.any(|l| l.objective.needs_collectibles())
+    }
+
+    pub fn wall_set(&self, level: &LevelSpec) -> &WallSet {
+        &self.wall_sets[&level.walls]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn objectives_decide_when_the_exit_opens_and_the_level_ends() {
+        #[derive(Deserialize)]
+        struct Only { objective: Objective }
+        let parse = |s: &str| toml::from_str::<Only>(&format!("objective = \"{}\"", s)).unwrap().objective;
+        assert_eq!(parse("collect_then_exit"), Objective::CollectThenExit);
+        assert_eq!(parse("reach_exit"), Objective::ReachExit);
+
+        // recoger: la salida no cuenta
+        assert!(Objective::Collect.complete(3, 3, false));
+        assert!(!Objective::Collect.exit_open(3, 3));
+        // recoger y salir: cerrada hasta tener los dulces, y hay que pisarla
+        let then_exit = Objective::CollectThenExit;
+        assert!(!then_exit.exit_open(2, 3) && then_exit.exit_open(3, 3));
+        assert!(!then_exit.complete(3, 3, false) && !then_exit.complete(2, 3, true));
+        assert!(then_exit.complete(3, 3, true));
+        // solo salir
+        assert!(Objective::ReachExit.complete(0, 3, true));
+    }
+}
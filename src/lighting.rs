@@ -0,0 +1,125 @@
+// lighting.rs
+// Modelo de luz común a paredes, suelo, techo y sprites: atenuación por
+// distancia, sombreado por cara y niebla. Las distancias van en píxeles del
+// mundo, igual que `Intersect::distance`.
+use raylib::prelude::Color;
+
+use crate::caster::Face;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lighting {
+    /// Cuánto se oscurece por píxel de distancia: luz = 1 / (1 + falloff * d).
+    pub falloff: f32,
+    /// Luz mínima, por lejos que esté algo.
+    pub min_light: f32,
+    /// Factor para las caras este/oeste; las norte/sur quedan en 1.
+    pub side_shade: f32,
+    pub fog_color: Color,
+    /// Niebla = 1 - e^(-densidad * d); 0 la desactiva.
+    pub fog_density: f32,
+}
+
+/// Distancia a la que se considera el cielo al aplicarle la niebla.
+const SKY_DISTANCE: f32 = 1000.0;
+
+/// Nombres que acepta `lighting.preset` en levels.toml.
+pub const PRESETS: &[&str] = &["day", "dark", "mist"];
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Lighting {
+            falloff: 0.0015,
+            min_light: 0.60,
+            side_shade: 0.80,
+            fog_color: Color::BLACK,
+            fog_density: 0.0,
+        }
+    }
+}
+
+impl Lighting {
+    pub fn preset(name: &str) -> Option<Lighting> {
+        match name {
+            "day" => Some(Lighting::default()),
+            // laberinto oscuro: casi nada se ve pasadas tres o cuatro casillas
+            "dark" => Some(Lighting {
+                falloff: 0.008,
+                min_light: 0.05,
+                side_shade: 0.70,
+                fog_color: Color::new(4, 2, 10, 255),
+                fog_density: 0.006,
+            }),
+            "mist" => Some(Lighting {
+                falloff: 0.002,
+                min_light: 0.50,
+                side_shade: 0.85,
+                fog_color: Color::new(240, 214, 228, 255),
+                fog_density: 0.003,
+            }),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn light(&self, distance: f32) -> f32 {
+        (1.0 / (1.0 + self.falloff * distance)).clamp(self.min_light, 1.0)
+    }
+
+    #[inline]
+    pub fn fog(&self, distance: f32) -> f32 {
+        if self.fog_density <= 0.0 { return 0.0; }
+        1.0 - (-self.fog_density * distance.max(0.0)).exp()
+    }
+
+    #[inline]
+    pub fn face_shade(&self, face: Face) -> f32 {
+        match face {
+            Face::North | Face::South => 1.0,
+            Face::East | Face::West => self.side_shade,
+        }
+    }
+
+    /// Color visto a `distance`, con el sombreado extra de la cara si es pared.
+    #[inline]
+    pub fn apply(&self, c: Color, distance: f32, face: Option<Face>) -> Color {
+        let light = self.light(distance) * face.map_or(1.0, |f| self.face_shade(f));
+        self.blend(c, light, self.fog(distance))
+    }
+
+    /// El cielo no se oscurece con la distancia, pero la niebla sí lo tapa.
+    #[inline]
+    pub fn sky(&self, c: Color) -> Color {
+        self.blend(c, 1.0, self.fog(SKY_DISTANCE))
+    }
+
+    #[inline]
+    fn blend(&self, c: Color, light: f32, fog: f32) -> Color {
+        let mix = |v: u8, f: u8| {
+            let lit = v as f32 * light;
+            (lit + (f as f32 - lit) * fog).round().clamp(0.0, 255.0) as u8
+        };
+        Color::new(mix(c.r, self.fog_color.r), mix(c.g, self.fog_color.g), mix(c.b, self.fog_color.b), c.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day_matches_old_wall_shading_and_fog_converges() {
+        let day = Lighting::default();
+        let c = Color::new(200, 100, 50, 255);
+        for d in [1.0f32, 64.0, 300.0, 5000.0] {
+            let old = (1.0 / (1.0 + 0.0015 * d)).clamp(0.60, 1.0);
+            let lit = day.apply(c, d, Some(Face::North));
+            assert!((lit.r as f32 - 200.0 * old).abs() <= 1.0, "d = {}", d);
+        }
+        assert!(day.apply(c, 64.0, Some(Face::East)).r < day.apply(c, 64.0, Some(Face::North)).r);
+
+        let dark = Lighting::preset("dark").unwrap();
+        let far = dark.apply(Color::WHITE, 10_000.0, None);
+        assert!(far.r.abs_diff(dark.fog_color.r) <= 1 && far.b.abs_diff(dark.fog_color.b) <= 1);
+        assert!(dark.apply(c, 128.0, None).r < day.apply(c, 128.0, None).r);
+    }
+}
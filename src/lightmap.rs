@@ -0,0 +1,158 @@
+// lightmap.rs
+// Luces puntuales del nivel horneadas en un mapa por casilla. Cada luz ilumina
+// las casillas dentro de su radio a las que llega un rayo sin chocar con pared.
+use raylib::prelude::{Color, Vector2};
+
+use crate::caster::cast_ray_from;
+use crate::door::Doors;
+use crate::maze::{Maze, is_walkable};
+
+/// Luz colocada en una casilla del laberinto (índices desde 0).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub cell: (usize, usize),
+    pub color: Color,
+    /// Alcance en casillas.
+    pub radius: f32,
+    pub intensity: f32,
+}
+
+/// Luz RGB de cada casilla, ya sumada con la luz ambiente.
+#[derive(Debug, Clone)]
+pub struct LightMap {
+    width: usize,
+    height: usize,
+    cells: Vec<[f32; 3]>,
+    walkable: Vec<bool>,
+    block_size: f32,
+}
+
+/// Tope de luz por canal, para que varias luces juntas no quemen el color.
+const MAX_LIGHT: f32 = 2.0;
+
+impl LightMap {
+    pub fn bake(maze: &Maze, lights: &[PointLight], ambient: f32, block_size: usize) -> LightMap {
+        let height = maze.len();
+        let width = maze.first().map_or(0, Vec::len);
+        let bs = block_size as f32;
+        let walkable: Vec<bool> = maze
+            .iter()
+            .flat_map(|row| (0..width).map(move |i| row.get(i).is_some_and(|&c| is_walkable(c))))
+            .collect();
+        let mut cells = vec![[ambient; 3]; width * height];
+
+        for light in lights {
+            let (li, lj) = light.cell;
+            if li >= width || lj >= height { continue; }
+            let origin = Vector2::new((li as f32 + 0.5) * bs, (lj as f32 + 0.5) * bs);
+            let reach = light.radius.ceil() as usize;
+
+            for j in lj.saturating_sub(reach)..(lj + reach + 1).min(height) {
+                for i in li.saturating_sub(reach)..(li + reach + 1).min(width) {
+                    let (dx, dy) = (i as f32 - li as f32, j as f32 - lj as f32);
+                    let d = (dx * dx + dy * dy).sqrt();
+                    if d >= light.radius { continue; }
+                    if (i, j) != (li, lj) && !visible(maze, origin, (i, j), block_size) { continue; }
+
+                    let k = light.intensity * (1.0 - d / light.radius).powi(2);
+                    let cell = &mut cells[j * width + i];
+                    cell[0] += k * light.color.r as f32 / 255.0;
+                    cell[1] += k * light.color.g as f32 / 255.0;
+                    cell[2] += k * light.color.b as f32 / 255.0;
+                }
+            }
+        }
+
+        for c in &mut cells {
+            for v in c.iter_mut() { *v = v.min(MAX_LIGHT); }
+        }
+        LightMap { width, height, cells, walkable, block_size: bs }
+    }
+
+    /// Luz en un punto del mundo, interpolada entre los centros de las casillas
+    /// caminables vecinas; las paredes no cuentan para no filtrar luz a través.
+    pub fn sample(&self, x: f32, y: f32) -> [f32; 3] {
+        let gx = x / self.block_size - 0.5;
+        let gy = y / self.block_size - 0.5;
+        let (i0, j0) = (gx.floor(), gy.floor());
+        let (fx, fy) = (gx - i0, gy - j0);
+
+        let mut sum = [0.0; 3];
+        let mut total = 0.0;
+        for (di, dj, w) in [(0, 0, (1.0 - fx) * (1.0 - fy)), (1, 0, fx * (1.0 - fy)), (0, 1, (1.0 - fx) * fy), (1, 1, fx * fy)] {
+            let (i, j) = (i0 as isize + di, j0 as isize + dj);
+            if i < 0 || j < 0 || i as usize >= self.width || j as usize >= self.height { continue; }
+            let idx = j as usize * self.width + i as usize;
+            if !self.walkable[idx] || w <= 0.0 { continue; }
+            for (s, v) in sum.iter_mut().zip(self.cells[idx]) { *s += v * w; }
+            total += w;
+        }
+
+        if total > 0.0 {
+            sum.map(|s| s / total)
+        } else {
+            // punto dentro de una pared: se usa su propia casilla
+            let (i, j) = ((x / self.block_size) as usize, (y / self.block_size) as usize);
+            self.cells.get(j * self.width + i).copied().unwrap_or([1.0; 3])
+        }
+    }
+
+    /// Multiplica un color por la luz de `sample`.
+    #[inline]
+    pub fn modulate(c: Color, light: [f32; 3]) -> Color {
+        let ch = |v: u8, l: f32| (v as f32 * l).round().clamp(0.0, 255.0) as u8;
+        Color::new(ch(c.r, light[0]), ch(c.g, light[1]), ch(c.b, light[2]), c.a)
+    }
+}
+
+/// Si un rayo desde `origin` llega a la casilla `target` sin chocar antes con otra pared.
+fn visible(maze: &Maze, origin: Vector2, (i, j): (usize, usize), block_size: usize) -> bool {
+    let bs = block_size as f32;
+    let tx = (i as f32 + 0.5) * bs;
+    let ty = (j as f32 + 0.5) * bs;
+    let (dx, dy) = (tx - origin.x, ty - origin.y);
+    let dist = (dx * dx + dy * dy).sqrt();
+    let a = dy.atan2(dx);
+    let hit = cast_ray_from(maze, origin, a, block_size, &Doors::default());
+
+    if is_walkable(maze[j][i]) {
+        hit.distance >= dist - 1e-3
+    } else {
+        // pared: basta con que el primer choque sea en esta misma casilla
+        let hx = hit.hit_x + a.cos() * 0.01;
+        let hy = hit.hit_y + a.sin() * 0.01;
+        (hx / bs).floor() as usize == i && (hy / bs).floor() as usize == j
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(text: &str) -> Maze {
+        text.lines().map(|l| l.chars().collect()).collect()
+    }
+
+    #[test]
+    fn walls_block_light_and_ambient_fills_the_rest() {
+        let m = maze("+-------+\n\
+                      |   |   |\n\
+                      +-------+");
+        let red = PointLight { cell: (1, 1), color: Color::new(255, 0, 0, 255), radius: 6.0, intensity: 1.0 };
+        let map = LightMap::bake(&m, &[red], 0.2, 64);
+
+        let at = |i: usize, j: usize| map.cells[j * map.width + i];
+        assert!(at(1, 1)[0] > 1.0);
+        assert!(at(3, 1)[0] > at(3, 1)[1]);
+        // detrás del '|' solo queda la luz ambiente
+        assert_eq!(at(5, 1), [0.2; 3]);
+        assert_eq!(at(7, 1), [0.2; 3]);
+        // la cara del '|' que mira a la luz sí se ilumina
+        assert!(at(4, 1)[0] > 0.2);
+
+        let near_wall = map.sample(4.0 * 64.0 - 1.0, 1.5 * 64.0);
+        let far_side = map.sample(5.0 * 64.0 + 1.0, 1.5 * 64.0);
+        assert!(near_wall[0] > 0.2);
+        assert_eq!(far_side, [0.2; 3]);
+    }
+}
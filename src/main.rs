@@ -1,37 +1,57 @@
 mod framebuffer;
-mod line;
 mod caster;
 mod texture;
 mod maze;
 mod player;
 mod sprite;
 mod sound;
+mod display;
+mod render;
+mod levels;
+mod mazegen;
+mod endless;
+mod flashlight;
+mod door;
+mod enemy;
+mod hint;
+mod pathfind;
+mod lighting;
+mod lightmap;
+mod settings;
+mod camera;
+mod input;
+mod pause;
+mod scene;
+mod screens;
+mod playing;
+#[cfg(test)]
+mod golden_tests;
 
 use raylib::prelude::*;
-use std::f32::consts::PI;
 
 use crate::framebuffer::Framebuffer;
-use crate::caster::cast_ray;
 use crate::texture::TextureManager;
-use crate::maze::{Maze, load_maze, find_char};
-use crate::player::{Player, process_events};
-use crate::sprite::{Sprite, draw_sprite};
+use crate::maze::{Maze, MazeError, load_maze, read_maze, validate, analyze, find_char};
+use crate::player::Player;
+use crate::sprite::Sprite;
 use crate::sound::SoundManager;
-
-#[derive(Clone, Copy)]
-struct LevelSpec { file: &'static str, time_limit: i32 }
-
-static LEVELS: &[LevelSpec] = &[
-    LevelSpec { file: "maze.txt",  time_limit: 60 },
-    LevelSpec { file: "maze2.txt", time_limit: 60 },
-];
+use crate::display::{Display, OffscreenDisplay, ScreenTexture};
+use crate::render::{Scenery, render_frame};
+use crate::levels::{LevelSpec, Manifest, Objective, MANIFEST_FILE};
+use crate::mazegen::{GenOptions, generate, maze_to_string};
+use crate::endless::{Level, endless_level};
+use crate::settings::{Settings, SETTINGS_FILE};
+use crate::camera::Camera;
+use crate::input::Input;
+use crate::scene::{Game, Scene};
+use crate::screens::{LoadError, Title};
 
 fn load_sprites_from_maze(maze: &Maze, block: usize) -> Vec<Sprite> {
     let mut v = Vec::new();
     for (j, row) in maze.iter().enumerate() {
         for (i, &c) in row.iter().enumerate() {
             match c {
-                'b' | 'V' => v.push(Sprite::new_cell(i, j, block, c)),
+                'b' | 'V' | 'B' | 'E' | 'r' | 'a' | 'm' => v.push(Sprite::new_cell(i, j, block, c)),
                 _ => {}
             }
         }
@@ -39,453 +59,265 @@ fn load_sprites_from_maze(maze: &Maze, block: usize) -> Vec<Sprite> {
     v
 }
 
-fn show_success_screen(
-    rl: &mut RaylibHandle,
-    th: &RaylibThread,
-    tex: &Texture2D,
-    screen_w: i32,
-    screen_h: i32,
-) {
-    loop {
-        {
-            let mut d = rl.begin_drawing(th);
-            d.clear_background(Color::BLACK);
-
-            let scale = (screen_w as f32 / tex.width as f32)
-                .max(screen_h as f32 / tex.height as f32);
-            let dest_w = tex.width as f32 * scale;
-            let dest_h = tex.height as f32 * scale;
-
-            let src = Rectangle::new(0.0, 0.0, tex.width as f32, tex.height as f32);
-            let dest = Rectangle::new(
-                (screen_w as f32 - dest_w) * 0.5,
-                (screen_h as f32 - dest_h) * 0.5,
-                dest_w,
-                dest_h,
-            );
-            d.draw_texture_pro(tex, src, dest, Vector2::zero(), 0.0, Color::WHITE);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) || rl.window_should_close() { break; }
-    }
+fn prepare_level(file: &str, block_size: usize) -> Result<(Maze, Vec<Sprite>, Vector2), MazeError> {
+    setup_level(load_maze(file, true)?, block_size)
 }
 
-fn show_lost_screen(
-    rl: &mut RaylibHandle,
-    th: &RaylibThread,
-    tex: &Texture2D,
-    screen_w: i32,
-    screen_h: i32,
-) {
-    loop {
-        {
-            let mut d = rl.begin_drawing(th);
-            d.clear_background(Color::BLACK);
-
-            let scale = (screen_w as f32 / tex.width as f32)
-                .max(screen_h as f32 / tex.height as f32);
-            let dest_w = tex.width as f32 * scale;
-            let dest_h = tex.height as f32 * scale;
-
-            let src = Rectangle::new(0.0, 0.0, tex.width as f32, tex.height as f32);
-            let dest = Rectangle::new(
-                (screen_w as f32 - dest_w) * 0.5,
-                (screen_h as f32 - dest_h) * 0.5,
-                dest_w,
-                dest_h,
-            );
-            d.draw_texture_pro(tex, src, dest, Vector2::zero(), 0.0, Color::WHITE);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) || rl.window_should_close() { break; }
-    }
-}
+/// Saca inicio, dulces, llaves y enemigos del laberinto y deja esas casillas libres.
+fn setup_level(mut maze: Maze, block_size: usize) -> Result<(Maze, Vec<Sprite>, Vector2), MazeError> {
 
-fn show_start_screen(
-    rl: &mut RaylibHandle,
-    th: &RaylibThread,
-    tex: &Texture2D,
-    screen_w: i32,
-    screen_h: i32,
-) {
-    loop {
-        {
-            let mut d = rl.begin_drawing(th);
-            d.clear_background(Color::BLACK);
-
-            let scale = (screen_w as f32 / tex.width as f32)
-                .max(screen_h as f32 / tex.height as f32);
-            let dest_w = tex.width as f32 * scale;
-            let dest_h = tex.height as f32 * scale;
-
-            let src = Rectangle::new(0.0, 0.0, tex.width as f32, tex.height as f32);
-            let dest = Rectangle::new(
-                (screen_w as f32 - dest_w) * 0.5,
-                (screen_h as f32 - dest_h) * 0.5,
-                dest_w,
-                dest_h,
-            );
-            d.draw_texture_pro(tex, src, dest, Vector2::zero(), 0.0, Color::WHITE);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) || rl.window_should_close() { break; }
-    }
-}
+    let (pi, pj) = find_char(&maze, 'p').ok_or(MazeError::NoSpawn)?;
+    let spawn = Vector2::new(
+        (pi * block_size + block_size / 2) as f32,
+        (pj * block_size + block_size / 2) as f32,
+    );
 
-fn show_level_select_screen(
-    rl: &mut RaylibHandle,
-    th: &RaylibThread,
-    tex: &Texture2D,
-    screen_w: i32,
-    screen_h: i32,
-) -> usize {
-    loop {
-        {
-            let mut d = rl.begin_drawing(th);
-            d.clear_background(Color::BLACK);
-
-            let scale = (screen_w as f32 / tex.width as f32)
-                .max(screen_h as f32 / tex.height as f32);
-            let dest_w = tex.width as f32 * scale;
-            let dest_h = tex.height as f32 * scale;
-
-            let src = Rectangle::new(0.0, 0.0, tex.width as f32, tex.height as f32);
-            let dest = Rectangle::new(
-                (screen_w as f32 - dest_w) * 0.5,
-                (screen_h as f32 - dest_h) * 0.5,
-                dest_w,
-                dest_h,
-            );
-            d.draw_texture_pro(tex, src, dest, Vector2::zero(), 0.0, Color::WHITE);
-        }
+    let sprites = load_sprites_from_maze(&maze, block_size);
 
-        if rl.is_key_pressed(KeyboardKey::KEY_ONE) { return 0; }
-        if rl.is_key_pressed(KeyboardKey::KEY_TWO) { return 1; }
-        if rl.window_should_close() { return 0; }
+    for row in maze.iter_mut() {
+        for c in row.iter_mut() {
+            if matches!(*c, 'p' | 'V' | 'b' | 'B' | 'E' | '1' | '2' | '3' | 'r' | 'a' | 'm') { *c = ' '; }
+        }
     }
-}
 
-#[derive(Clone)]
-struct Candy { i: usize, j: usize, collected: bool }
-
-struct Level { number: u32, candies_needed: u32, duration_secs: i32 }
-
-struct GameState {
-    level: Level,
-    candies: Vec<Candy>,
-    level_deadline: f64,
-    paused: bool,
-    msg_text: Option<String>,
-    msg_until: f64,
-    pause_started: f64,
-    pending_action: Option<PendingAction>,
+    Ok((maze, sprites, spawn))
 }
 
-enum PendingAction { NextLevel, RestartLevel }
+/// Qué se está jugando: un nivel del manifiesto o uno de la partida infinita.
+#[derive(Clone, Copy, PartialEq)]
+enum Stage {
+    Manifest(usize),
+    Endless { seed: u64, number: u32 },
+}
 
-fn spawn_candies(maze: &Maze, n: u32) -> Vec<Candy> {
-    let mut spaces = Vec::new();
-    for (j, row) in maze.iter().enumerate() {
-        for (i, &c) in row.iter().enumerate() {
-            if c == ' ' || c == 'g' { spaces.push((i, j)); }
+impl Stage {
+    /// Nivel del manifiesto del que se toman paredes, colores y música.
+    fn look(&self, level_count: usize) -> usize {
+        match *self {
+            Stage::Manifest(idx) => idx,
+            Stage::Endless { number, .. } => (number.max(1) as usize - 1) % level_count,
         }
     }
-    let mut out = Vec::new();
-    if spaces.is_empty() { return out; }
-    let step = (spaces.len() as u32 / n.max(1)).max(1);
-    let mut idx = step / 2;
-    while out.len() < n as usize {
-        let (i, j) = spaces[(idx as usize) % spaces.len()];
-        if !out.iter().any(|c| c.i == i && c.j == j) {
-            out.push(Candy { i, j, collected: false });
+
+    fn file(&self, levels: &[LevelSpec]) -> String {
+        match *self {
+            Stage::Manifest(idx) => levels[idx].maze.clone(),
+            Stage::Endless { seed, number } => format!("nivel infinito {} (semilla {})", number, seed),
         }
-        idx = idx.wrapping_add(step);
     }
-    out
 }
 
-fn player_cell(player: &Player, block_size: usize) -> (usize, usize) {
-    ((player.pos.x as usize) / block_size, (player.pos.y as usize) / block_size)
+struct LoadedLevel {
+    maze: Maze,
+    sprites: Vec<Sprite>,
+    spawn: Vector2,
+    deadline: f64,
+    level: Level,
+    /// En el modo infinito siempre es recoger los dulces.
+    objective: Objective,
 }
 
-fn render_world(
-    framebuffer: &mut Framebuffer,
-    maze: &Maze,
+fn load_level(
+    stage: Stage,
+    levels: &[LevelSpec],
     block_size: usize,
-    player: &Player,
-    depth_buffer: &mut [f32],
-    texman: &mut TextureManager,
-) {
-    let num_rays = framebuffer.width;
-    let hw = framebuffer.width as f32 / 2.0;
-    let hh = framebuffer.height as f32 / 2.0;
-    let dist_proj_plane = hw / (player.fov * 0.5).tan();
-
-    assert_eq!(depth_buffer.len(), num_rays as usize);
-
-    for i in 0..num_rays {
-        let t = i as f32 / num_rays as f32;
-        let a = player.a - (player.fov * 0.5) + (player.fov * t);
-
-        let hit = cast_ray(framebuffer, maze, player, a, block_size, false);
-        let corrected = (hit.distance * (a - player.a).cos()).max(1.0);
-        depth_buffer[i as usize] = corrected;
-
-        let stake_h = (block_size as f32 * dist_proj_plane) / corrected;
-        let top = (hh - stake_h * 0.5).max(0.0) as u32;
-        let bot = (hh + stake_h * 0.5).min(framebuffer.height as f32 - 1.0) as u32;
-
-        let sky_col   = Color::new(0xC7, 0xD9, 0xDD, 255);
-        let floor_col = Color::new(255, 170, 170, 255);
-
-        framebuffer.set_current_color(sky_col);
-        for y in 0..top { framebuffer.set_pixel(i, y); }
-
-        let fx = (hit.hit_x as f32) / (block_size as f32);
-        let fy = (hit.hit_y as f32) / (block_size as f32);
-        let frac_x = fx - fx.floor();
-        let frac_y = fy - fy.floor();
-        let near_edge_x = frac_x < 0.001 || frac_x > 0.999;
-        let near_edge_y = frac_y < 0.001 || frac_y > 0.999;
-
-        let u = if near_edge_x && !near_edge_y { frac_y }
-                else if near_edge_y && !near_edge_x { frac_x }
-                else { if (frac_x - 0.5).abs() > (frac_y - 0.5).abs() { frac_y } else { frac_x } };
-
-        let ch = match hit.impact { '+' | '-' | '|' | 'g' => hit.impact, _ => '#' };
-
-        for y in top..=bot {
-            let v = (y as f32 - top as f32) / (bot.saturating_sub(top).max(1) as f32);
-            let mut color = texman.sample_uv(ch, u, v);
-            let shade = (1.0 / (1.0 + 0.0015 * corrected)).clamp(0.60, 1.0);
-            color.r = ((color.r as f32) * shade) as u8;
-            color.g = ((color.g as f32) * shade) as u8;
-            color.b = ((color.b as f32) * shade) as u8;
-            framebuffer.set_current_color(color);
-            framebuffer.set_pixel(i, y);
+    rl: &RaylibHandle,
+) -> Result<LoadedLevel, MazeError> {
+    let (mut level, maze) = match stage {
+        Stage::Manifest(idx) => {
+            let spec = &levels[idx];
+            let level = Level { number: idx as u32 + 1, candies_needed: 0, duration_secs: spec.time_limit };
+            (level, load_maze(&spec.maze, spec.objective.needs_collectibles())?)
         }
-
-        framebuffer.set_current_color(floor_col);
-        for y in (bot+1)..(framebuffer.height) { framebuffer.set_pixel(i, y); }
+        Stage::Endless { seed, number } => endless_level(seed, number),
+    };
+    let (maze, sprites, spawn) = setup_level(maze, block_size)?;
+    let mut objective = Objective::Collect;
+    if let Stage::Manifest(idx) = stage {
+        level.candies_needed = levels[idx].candies_needed(sprites.iter().filter(|s| s.is_candy()).count() as u32);
+        objective = levels[idx].objective;
+        if objective.needs_exit() && find_char(&maze, 'g').is_none() { return Err(MazeError::NoExit); }
     }
+    let deadline = rl.get_time() + level.duration_secs as f64;
+    Ok(LoadedLevel { maze, sprites, spawn, deadline, level, objective })
 }
 
-fn render_minimap(
-    fb: &mut Framebuffer,
-    maze: &Maze,
-    block_size: usize,
-    player: &Player,
-    sprites: &[Sprite],
-) {
-    let mini = 8usize;
-    let ox = 10usize;
-    let oy = 10usize;
-
-    let w = maze[0].len() * mini;
-    let h = maze.len() * mini;
-    fb.set_current_color(Color::new(20, 20, 30, 255));
-    for x in ox..ox + w { for y in oy..oy + h { fb.set_pixel(x as u32, y as u32); } }
+const SCREEN_W: u32 = 800;
+const SCREEN_H: u32 = 600;
+const BLOCK_SIZE: usize = 64;
 
-    for (j, row) in maze.iter().enumerate() {
-        for (i, &c) in row.iter().enumerate() {
-            if c == ' ' { continue; }
-            let x0 = ox + i * mini;
-            let y0 = oy + j * mini;
-            let col = match c { '+'|'-'|'|' => Color::DARKPURPLE, 'g' => Color::GREEN, _ => Color::GRAY };
-            fb.set_current_color(col);
-            for x in x0..x0 + mini { for y in y0..y0 + mini { fb.set_pixel(x as u32, y as u32); } }
-        }
-    }
-
-    for s in sprites.iter().filter(|s| !s.collected) {
-        let i = (s.x as usize) / block_size;
-        let j = (s.y as usize) / block_size;
-        let x0 = ox + i * mini;
-        let y0 = oy + j * mini;
-        fb.set_current_color(Color::GRAY);
-        for x in x0..x0 + mini { for y in y0..y0 + mini { fb.set_pixel(x as u32, y as u32); } }
-    }
+/// `--render <laberinto> <x> <y> <ángulo> <salida.ppm>`: dibuja un frame sin abrir
+/// ventana. La posición va en celdas y el ángulo en radianes.
+fn render_headless(args: &[String]) -> Result<(), String> {
+    let [file, x, y, a, out] = args else {
+        return Err("uso: --render <laberinto> <x> <y> <angulo> <salida.ppm>".to_string());
+    };
+    let parse = |v: &str| v.parse::<f32>().map_err(|_| format!("número inválido: {}", v));
 
-    let scale = mini as f32 / block_size as f32;
-    let jx = ox as f32 + player.pos.x * scale;
-    let jy = oy as f32 + player.pos.y * scale;
-    fb.draw_circle_filled(jx.round() as i32, jy.round() as i32, 4, Color::RED);
-}
+    let (maze, sprites, _) = prepare_level(file, BLOCK_SIZE).map_err(|e| format!("{}: {}", file, e))?;
+    let player = Player::new(Vector2::new(parse(x)? * BLOCK_SIZE as f32, parse(y)? * BLOCK_SIZE as f32), parse(a)?);
 
-fn load_level(
-    idx: usize,
-    block_size: usize,
-    rl: &RaylibHandle,
-) -> (Maze, Vec<Sprite>, Vector2, f64) {
-    let mut maze = load_maze(LEVELS[idx].file);
+    let texman = TextureManager::new_headless();
+    let mut framebuffer = Framebuffer::new(SCREEN_W, SCREEN_H);
+    let mut depth_buffer = vec![f32::INFINITY; SCREEN_W as usize];
+    let mut display = OffscreenDisplay::new();
 
-    let (pi, pj) = find_char(&maze, 'p').unwrap_or((1, 1));
-    let spawn = Vector2::new(
-        (pi * block_size + block_size / 2) as f32,
-        (pj * block_size + block_size / 2) as f32,
-    );
+    framebuffer.clear();
+    render_frame(&mut framebuffer, &maze, BLOCK_SIZE, &player, &sprites, &mut depth_buffer, &texman, &Scenery::default());
+    display.present(&framebuffer, None, None);
+    display.save_ppm(out).map_err(|e| format!("{}: {}", out, e))
+}
 
-    let sprites = load_sprites_from_maze(&maze, block_size);
+/// `--check <archivo|carpeta>...`: revisa que cada nivel se pueda completar.
+/// En una carpeta revisa todos los `.txt`. Devuelve si todos están bien.
+fn check_levels(paths: &[String]) -> bool {
+    let mut files = Vec::new();
+    for path in paths {
+        match std::fs::read_dir(path) {
+            Ok(entries) => {
+                let mut found: Vec<_> = entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.extension().is_some_and(|ext| ext == "txt"))
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .collect();
+                found.sort();
+                files.extend(found);
+            }
+            Err(_) => files.push(path.clone()),
+        }
+    }
 
-    for row in maze.iter_mut() {
-        for c in row.iter_mut() {
-            if matches!(*c, 'p' | 'V' | 'b' | '1' | '2' | '3') { *c = ' '; }
+    // el objetivo del nivel dice si hacen falta dulces; sin manifiesto, siempre
+    let manifest = Manifest::load(MANIFEST_FILE).unwrap_or_default();
+    let mut all_ok = true;
+    for file in &files {
+        let maze = match read_maze(file) {
+            Ok(maze) => maze,
+            Err(e) => { all_ok = false; println!("{}: {}", file, e); continue; }
+        };
+
+        match validate(&maze, manifest.needs_collectibles(file)) {
+            Ok(()) => println!("{}: ok", file),
+            Err(e) => { all_ok = false; println!("{}: {}", file, e); }
+        }
+        if let Ok(report) = analyze(&maze) {
+            for line in report.to_string().lines() { println!("  {}", line); }
         }
     }
+    all_ok
+}
 
-    let deadline = rl.get_time() + LEVELS[idx].time_limit as f64;
-    (maze, sprites, spawn, deadline)
+/// `--generate <algoritmo> <ancho> <alto> <semilla> <salida.txt> [dulces] [trenzado]`:
+/// escribe un laberinto nuevo. Ancho y alto van en celdas; el trenzado es la
+/// fracción (0..1) de callejones sin salida que se abren.
+fn generate_level(args: &[String]) -> Result<(), String> {
+    let usage = "uso: --generate <backtracker|prim|kruskal|wilson> <ancho> <alto> <semilla> <salida.txt> [dulces] [trenzado]";
+    let (algorithm, width, height, seed, out, rest) = match args {
+        [a, w, h, s, o, rest @ ..] if rest.len() <= 2 => (a, w, h, s, o, rest),
+        _ => return Err(usage.to_string()),
+    };
+    let number = |v: &str| v.parse::<usize>().map_err(|_| format!("número inválido: {}", v));
+
+    let mut opts = GenOptions {
+        width: number(width)?,
+        height: number(height)?,
+        algorithm: algorithm.parse()?,
+        seed: seed.parse().map_err(|_| format!("semilla inválida: {}", seed))?,
+        ..GenOptions::default()
+    };
+    if opts.width < 2 || opts.height < 2 { return Err("el laberinto debe tener al menos 2x2 celdas".to_string()); }
+    if let Some(n) = rest.first() { opts.collectibles = number(n)?.max(1); }
+    if let Some(b) = rest.get(1) {
+        opts.braid = b.parse::<f32>().map_err(|_| format!("trenzado inválido: {}", b))?.clamp(0.0, 1.0);
+    }
+
+    let maze = generate(&opts);
+    std::fs::write(out, maze_to_string(&maze)).map_err(|e| format!("{}: {}", out, e))
 }
 
 fn main() {
-    const SCREEN_W: u32 = 800;
-    const SCREEN_H: u32 = 600;
-    const BLOCK_SIZE: usize = 64;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut seed: Option<u64> = None;
+    match args.first().map(String::as_str) {
+        Some("--render") => {
+            if let Err(e) = render_headless(&args[1..]) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("--generate") => {
+            if let Err(e) = generate_level(&args[1..]) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("--check") => {
+            let ok = check_levels(&args[1..]);
+            std::process::exit(if ok { 0 } else { 1 });
+        }
+        Some("--seed") => match args.get(1).map(|v| v.parse::<u64>()) {
+            Some(Ok(n)) => seed = Some(n),
+            _ => {
+                eprintln!("uso: --seed <número>");
+                std::process::exit(1);
+            }
+        },
+        _ => {}
+    }
 
     let (mut rl, raylib_thread) = raylib::init()
         .size(SCREEN_W as i32, SCREEN_H as i32)
         .title("Candy Maze")
         .build();
+    // ESC abre el menú de pausa en vez de cerrar la ventana
+    rl.set_exit_key(None);
 
-    let exito_tex: Texture2D = rl
-        .load_texture(&raylib_thread, "assets/exito.png")
-        .expect("No se pudo cargar assets/exito.png");
+    let settings = Settings::load(SETTINGS_FILE);
 
     let inicio_tex: Texture2D = rl
         .load_texture(&raylib_thread, "assets/inicio.png")
         .expect("No se pudo cargar assets/inicio.png");
-    show_start_screen(&mut rl, &raylib_thread, &inicio_tex, SCREEN_W as i32, SCREEN_H as i32);
-
-    let lost_tex: Texture2D = rl
-        .load_texture(&raylib_thread, "assets/lost.png")
-        .expect("No se pudo cargar assets/lost.png");
-
-    let mut sound_manager = SoundManager::new();
-
-    let mut framebuffer = Framebuffer::new(SCREEN_W, SCREEN_H);
-
-    // Level selection moved after start screen.
 
     let niveles_tex: Texture2D = rl
         .load_texture(&raylib_thread, "assets/niveles.jpeg")
         .expect("No se pudo cargar assets/niveles.jpeg");
 
-    let mut current_level: usize = show_level_select_screen(
-        &mut rl,
-        &raylib_thread,
-        &niveles_tex,
-        SCREEN_W as i32,
-        SCREEN_H as i32,
-    );
-
-    let (mut maze, mut sprites, spawn, mut deadline) = load_level(current_level, BLOCK_SIZE, &rl);
-
-    let mut player = Player { pos: spawn, a: -PI / 2.0, fov: PI / 3.0 };
+    let exito_tex: Texture2D = rl
+        .load_texture(&raylib_thread, "assets/exito.png")
+        .expect("No se pudo cargar assets/exito.png");
 
-    let mut texman = TextureManager::new(&mut rl, &raylib_thread);
+    let lost_tex: Texture2D = rl
+        .load_texture(&raylib_thread, "assets/lost.png")
+        .expect("No se pudo cargar assets/lost.png");
 
-    let mut state = GameState {
-        level: Level { number: 1, candies_needed: 5, duration_secs: 60 },
-        candies: Vec::new(),
-        level_deadline: deadline,
-        paused: false,
-        msg_text: None,
-        msg_until: 0.0,
-        pause_started: 0.0,
-        pending_action: None,
+    let (manifest, first, fatal): (Manifest, Box<dyn Scene>, bool) = match Manifest::load(MANIFEST_FILE) {
+        Ok(m) => (m, Box::new(Title), false),
+        Err(e) => (Manifest::default(), Box::new(LoadError::fatal(MANIFEST_FILE, &e)), true),
     };
 
-    let mut depth_buffer: Vec<f32> = vec![f32::INFINITY; framebuffer.width as usize];
-
-    while !rl.window_should_close() {
-        framebuffer.clear();
-        sound_manager.update();
-
-        let now = rl.get_time();
-        if state.paused {
-            if now >= state.msg_until {
-                let paused_delta = now - state.pause_started;
-                state.level_deadline += paused_delta;
-                state.paused = false;
-                state.msg_text = None;
-
-                if let Some(action) = state.pending_action.take() {
-                    match action {
-                        PendingAction::NextLevel => {
-                            current_level = (current_level + 1) % LEVELS.len();
-                            let (m2, s2, spawn2, dl2) = load_level(current_level, BLOCK_SIZE, &rl);
-                            maze = m2; sprites = s2; state.level_deadline = dl2;
-                            player.pos = spawn2; player.a = -PI/2.0;
-                        }
-                        PendingAction::RestartLevel => {
-                            let (m2, s2, spawn2, dl2) = load_level(current_level, BLOCK_SIZE, &rl);
-                            maze = m2; sprites = s2; state.level_deadline = dl2;
-                            player.pos = spawn2; player.a = -PI/2.0;
-                        }
-                    }
-                }
-            }
-        } else {
-            process_events(&mut player, &rl, &maze, BLOCK_SIZE);
-
-            let (ci, cj) = player_cell(&player, BLOCK_SIZE);
-            for s in &mut sprites {
-                let si = (s.x as usize) / BLOCK_SIZE;
-                let sj = (s.y as usize) / BLOCK_SIZE;
-                if !s.collected && si == ci && sj == cj {
-                    s.collected = true;
-                    sound_manager.play_piece();
-                }
-            }
-
-            let total_sprites = sprites.len() as u32;
-            let collected = sprites.iter().filter(|s| s.collected).count() as u32;
-            let remaining = (state.level_deadline - now).ceil() as i32;
-
-            if total_sprites > 0 && collected >= total_sprites && remaining >= 0 {
-                show_success_screen(&mut rl, &raylib_thread, &exito_tex, SCREEN_W as i32, SCREEN_H as i32);
-
-                if current_level + 1 >= LEVELS.len() {
-                    show_start_screen(&mut rl, &raylib_thread, &inicio_tex, SCREEN_W as i32, SCREEN_H as i32);
-                    let chosen = show_level_select_screen(
-                        &mut rl,
-                        &raylib_thread,
-                        &niveles_tex,
-                        SCREEN_W as i32,
-                        SCREEN_H as i32,
-                    );
-                    current_level = chosen;
-                    let (m2, s2, spawn2, dl2) = load_level(current_level, BLOCK_SIZE, &rl);
-                    maze = m2; sprites = s2; state.level_deadline = dl2;
-                    player.pos = spawn2; player.a = -PI/2.0;
-                } else {
-                    current_level = (current_level + 1) % LEVELS.len();
-                    let (m2, s2, spawn2, dl2) = load_level(current_level, BLOCK_SIZE, &rl);
-                    maze = m2; sprites = s2; state.level_deadline = dl2;
-                    player.pos = spawn2; player.a = -PI/2.0;
-                }
-            } else if remaining < 0 {
-                show_lost_screen(&mut rl, &raylib_thread, &lost_tex, SCREEN_W as i32, SCREEN_H as i32);
-
-                let (m2, s2, spawn2, dl2) = load_level(current_level, BLOCK_SIZE, &rl);
-                maze = m2; sprites = s2; state.level_deadline = dl2;
-                player.pos = spawn2; player.a = -PI/2.0;
-            }
-        }
-
-        render_world(&mut framebuffer, &maze, BLOCK_SIZE, &player, &mut depth_buffer, &mut texman);
-
-        for s in sprites.iter().filter(|s| !s.collected) {
-            draw_sprite(&mut framebuffer, &player, s, &mut texman, &depth_buffer, BLOCK_SIZE);
-        }
-
-        render_minimap(&mut framebuffer, &maze, BLOCK_SIZE, &player, &sprites);
-
-        let hud = Some((
-            sprites.iter().filter(|s| s.collected).count() as u32,
-            sprites.len() as u32,
-            (state.level_deadline - rl.get_time()).ceil() as i32,
-        ));
-
-        framebuffer.swap_buffers_with_hud(&mut rl, &raylib_thread, hud, state.msg_text.as_deref());
+    let texman = TextureManager::new(&mut rl, &raylib_thread);
+    let mut game = Game {
+        thread: &raylib_thread,
+        input: Input::new(settings.input.clone()),
+        camera: Camera::new(settings.camera),
+        settings,
+        sound: SoundManager::new(),
+        manifest,
+        seed,
+        inicio_tex,
+        niveles_tex,
+        exito_tex,
+        lost_tex,
+        texman,
+        applied_look: None,
+        framebuffer: Framebuffer::new(SCREEN_W, SCREEN_H),
+        depth_buffer: vec![f32::INFINITY; SCREEN_W as usize],
+        screen: ScreenTexture::default(),
+        show_map: true,
+    };
 
-        std::thread::sleep(std::time::Duration::from_millis(16));
-    }
-}
\ No newline at end of file
+    scene::run(&mut rl, &mut game, first);
+    if fatal { std::process::exit(1); }
+}
@@ -1,17 +1,350 @@
 // maze.rs
+use std::collections::VecDeque;
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader};
+
+use crate::door::{door_for, is_door, is_key, unlock_reachable};
+use crate::pathfind::{DistanceField, Pathfinder};
 
 pub type Maze = Vec<Vec<char>>;
 
-pub fn load_maze(filename: &str) -> Maze {
-    let file = File::open(filename).unwrap();
+/// Casillas que puede contener un archivo de nivel.
+const KNOWN_TILES: &[char] = &[
+    '+', '-', '|', ' ', 'p', 'g', 'b', 'V', 'B', 'E', '1', '2', '3', 'r', 'a', 'm', 'R', 'A', 'M',
+];
+
+/// Error al cargar un laberinto. Las líneas y columnas empiezan en 1.
+#[derive(Debug)]
+pub enum MazeError {
+    Io { path: String, source: io::Error },
+    Empty,
+    RaggedRow { line: usize, expected: usize, found: usize },
+    UnknownTile { line: usize, column: usize, tile: char },
+    NoSpawn,
+    MultipleSpawns { line: usize, column: usize, first_line: usize, first_column: usize },
+    UnreachableGoal { line: usize, column: usize },
+    UnreachableCollectible { line: usize, column: usize },
+    NoCollectibles,
+    /// El objetivo del nivel pide una salida y el laberinto no tiene.
+    NoExit,
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MazeError::Io { path, source } =>
+                write!(f, "no se pudo leer el archivo ({}): {}", path, source),
+            MazeError::Empty =>
+                write!(f, "el archivo está vacío"),
+            MazeError::RaggedRow { line, expected, found } =>
+                write!(f, "línea {}: tiene {} columnas, se esperaban {}", line, found, expected),
+            MazeError::UnknownTile { line, column, tile } =>
+                write!(f, "línea {}, columna {}: casilla desconocida '{}'", line, column, tile),
+            MazeError::NoSpawn =>
+                write!(f, "no hay punto de inicio 'p'"),
+            MazeError::MultipleSpawns { line, column, first_line, first_column } =>
+                write!(f, "línea {}, columna {}: segundo inicio 'p' (el primero está en línea {}, columna {})",
+                       line, column, first_line, first_column),
+            MazeError::UnreachableGoal { line, column } =>
+                write!(f, "línea {}, columna {}: la meta 'g' no se puede alcanzar desde el inicio", line, column),
+            MazeError::UnreachableCollectible { line, column } =>
+                write!(f, "línea {}, columna {}: este dulce no se puede alcanzar desde el inicio", line, column),
+            MazeError::NoCollectibles =>
+                write!(f, "no hay dulces ('b' o 'V') que recoger"),
+            MazeError::NoExit =>
+                write!(f, "el objetivo del nivel pide una salida 'g' y no hay"),
+        }
+    }
+}
+
+impl std::error::Error for MazeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MazeError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+pub fn load_maze(filename: &str, needs_collectibles: bool) -> Result<Maze, MazeError> {
+    let maze = read_maze(filename)?;
+    validate(&maze, needs_collectibles)?;
+    Ok(maze)
+}
+
+/// Lee el archivo tal cual, sin comprobar que el nivel sea jugable.
+pub fn read_maze(filename: &str) -> Result<Maze, MazeError> {
+    let io_err = |source| MazeError::Io { path: filename.to_string(), source };
+    let file = File::open(filename).map_err(io_err)?;
     let reader = BufReader::new(file);
 
-    reader
-        .lines()
-        .map(|line| line.unwrap().chars().collect())
-        .collect()
+    let mut maze: Maze = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(io_err)?;
+        maze.push(line.trim_end_matches('\r').chars().collect());
+    }
+    while maze.last().is_some_and(|row| row.is_empty()) { maze.pop(); }
+
+    Ok(maze)
+}
+
+/// Revisa forma, casillas, inicio, meta y dulces de un laberinto ya leído.
+/// `needs_collectibles` es falso en los niveles en que solo hay que llegar a la salida.
+pub fn validate(maze: &Maze, needs_collectibles: bool) -> Result<(), MazeError> {
+    let expected = maze.first().map(Vec::len).ok_or(MazeError::Empty)?;
+    let mut spawn: Option<(usize, usize)> = None;
+    let mut goal: Option<(usize, usize)> = None;
+    let mut collectibles = 0;
+
+    for (j, row) in maze.iter().enumerate() {
+        if row.len() != expected {
+            return Err(MazeError::RaggedRow { line: j + 1, expected, found: row.len() });
+        }
+        for (i, &c) in row.iter().enumerate() {
+            if !KNOWN_TILES.contains(&c) {
+                return Err(MazeError::UnknownTile { line: j + 1, column: i + 1, tile: c });
+            }
+            match c {
+                'p' => {
+                    if let Some((fi, fj)) = spawn {
+                        return Err(MazeError::MultipleSpawns {
+                            line: j + 1, column: i + 1,
+                            first_line: fj + 1, first_column: fi + 1,
+                        });
+                    }
+                    spawn = Some((i, j));
+                }
+                'g' => { goal.get_or_insert((i, j)); }
+                'b' | 'V' => collectibles += 1,
+                _ => {}
+            }
+        }
+    }
+
+    let spawn = spawn.ok_or(MazeError::NoSpawn)?;
+    if needs_collectibles && collectibles == 0 { return Err(MazeError::NoCollectibles); }
+
+    // las puertas cuentan como abiertas si se llega a su llave
+    let dist = unlock_reachable(maze, spawn).distance_field(spawn);
+    if let Some((gi, gj)) = goal && dist.get((gi, gj)).is_none() {
+        return Err(MazeError::UnreachableGoal { line: gj + 1, column: gi + 1 });
+    }
+    for (j, row) in maze.iter().enumerate() {
+        for (i, &c) in row.iter().enumerate() {
+            if matches!(c, 'b' | 'V') && dist.get((i, j)).is_none() {
+                return Err(MazeError::UnreachableCollectible { line: j + 1, column: i + 1 });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Casilla especial del nivel (dulce, meta...) con su posición en el archivo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement { pub tile: char, pub line: usize, pub column: usize }
+
+/// Zona caminable a la que no se llega desde el inicio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pocket { pub line: usize, pub column: usize, pub cells: usize }
+
+/// Resultado de recorrer el nivel desde `p`.
+#[derive(Debug, Default)]
+pub struct LevelReport {
+    pub unreachable: Vec<Placement>,
+    pub pockets: Vec<Pocket>,
+    /// Pasos de la ruta más corta que recoge todos los dulces y termina en la
+    /// meta (si la hay), con los desvíos para buscar llaves. Es exacta hasta
+    /// `EXACT_ROUTE_LIMIT` dulces y llaves; con más es una cota superior.
+    /// `None` si algo no se puede alcanzar.
+    pub route_length: Option<usize>,
+}
+
+impl LevelReport {
+    pub fn is_ok(&self) -> bool { self.unreachable.is_empty() }
+}
+
+impl fmt::Display for LevelReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for p in &self.unreachable {
+            writeln!(f, "línea {}, columna {}: '{}' no se puede alcanzar", p.line, p.column, p.tile)?;
+        }
+        for p in &self.pockets {
+            writeln!(f, "línea {}, columna {}: zona aislada de {} celdas", p.line, p.column, p.cells)?;
+        }
+        match self.route_length {
+            Some(n) => write!(f, "ruta más corta: {} pasos", n),
+            None => write!(f, "el nivel no se puede completar"),
+        }
+    }
+}
+
+const EXACT_ROUTE_LIMIT: usize = 12;
+
+/// Recorre el nivel desde el inicio con las reglas de `player::is_wall` y
+/// reporta dulces o meta inalcanzables, zonas aisladas y la ruta más corta.
+/// Para alcanzar, las puertas cuya llave se alcanza cuentan como abiertas; la
+/// ruta solo las cruza después de pasar por su llave.
+pub fn analyze(maze: &Maze) -> Result<LevelReport, MazeError> {
+    let spawn = find_char(maze, 'p').ok_or(MazeError::NoSpawn)?;
+    let paths = unlock_reachable(maze, spawn);
+    let dist = paths.distance_field(spawn);
+
+    let mut report = LevelReport::default();
+    let mut items = Vec::new();
+    let mut keys = Vec::new();
+    let mut goal = None;
+
+    for (j, row) in maze.iter().enumerate() {
+        for (i, &c) in row.iter().enumerate() {
+            if is_key(c) && dist.get((i, j)).is_some() { keys.push(((i, j), c)); }
+            if !matches!(c, 'b' | 'V' | 'B' | 'g') { continue; }
+            if dist.get((i, j)).is_none() {
+                report.unreachable.push(Placement { tile: c, line: j + 1, column: i + 1 });
+            } else if c == 'B' {
+                // las pilas no hace falta recogerlas
+            } else if c == 'g' {
+                goal.get_or_insert((i, j));
+            } else {
+                items.push((i, j));
+            }
+        }
+    }
+
+    report.pockets = pockets(maze, &paths, &dist);
+    if report.is_ok() {
+        report.route_length = Some(shortest_route(maze, spawn, &items, &keys, goal));
+    }
+    Ok(report)
+}
+
+/// Componentes caminables que el recorrido desde el inicio no tocó.
+fn pockets(maze: &Maze, paths: &Pathfinder, dist: &DistanceField) -> Vec<Pocket> {
+    let mut seen: Vec<Vec<bool>> = maze
+        .iter()
+        .enumerate()
+        .map(|(j, row)| (0..row.len()).map(|i| dist.get((i, j)).is_some()).collect())
+        .collect();
+    let mut out = Vec::new();
+
+    for (j, row) in maze.iter().enumerate() {
+        for (i, &c) in row.iter().enumerate() {
+            if !is_walkable(c) || seen[j][i] { continue; }
+            let mut cells = 0;
+            let mut queue = VecDeque::from([(i, j)]);
+            seen[j][i] = true;
+            while let Some(cell) = queue.pop_front() {
+                cells += 1;
+                for (ni, nj) in paths.neighbours(cell) {
+                    if !seen[nj][ni] {
+                        seen[nj][ni] = true;
+                        queue.push_back((ni, nj));
+                    }
+                }
+            }
+            out.push(Pocket { line: j + 1, column: i + 1, cells });
+        }
+    }
+    out
+}
+
+/// Ruta desde `start` que pasa por todos los `items` y acaba en `goal`. Una
+/// puerta solo se cruza después de pasar por una llave de su color, así que
+/// las llaves de `keys` son paradas opcionales. Todos los puntos deben ser
+/// alcanzables.
+fn shortest_route(
+    maze: &Maze,
+    start: (usize, usize),
+    items: &[(usize, usize)],
+    keys: &[((usize, usize), char)],
+    goal: Option<(usize, usize)>,
+) -> usize {
+    // un juego de caminos por cada combinación de colores de llave en mano
+    let mut colors: Vec<char> = keys.iter().map(|&(_, k)| door_for(k)).collect();
+    colors.sort_unstable();
+    colors.dedup();
+    let doors: Vec<((usize, usize), usize)> = maze
+        .iter()
+        .enumerate()
+        .flat_map(|(j, row)| row.iter().enumerate().map(move |(i, &c)| ((i, j), c)))
+        .filter(|&(_, c)| is_door(c))
+        .filter_map(|(cell, c)| colors.iter().position(|&d| d == c).map(|k| (cell, k)))
+        .collect();
+    let paths: Vec<Pathfinder> = (0..1usize << colors.len())
+        .map(|held| {
+            let mut p = Pathfinder::new(maze);
+            for &(cell, k) in &doors {
+                if held & (1 << k) != 0 { p.set_walkable(cell, true); }
+            }
+            p
+        })
+        .collect();
+
+    // paradas: primero los dulces, después las llaves
+    let n = items.len();
+    let stops: Vec<(usize, usize)> = items.iter().copied().chain(keys.iter().map(|&(cell, _)| cell)).collect();
+    let color_bit: Vec<usize> = keys
+        .iter()
+        .map(|&(_, k)| 1 << colors.iter().position(|&d| d == door_for(k)).unwrap_or(0))
+        .collect();
+    let held = |mask: usize| (n..stops.len()).filter(|&k| mask & (1 << k) != 0).fold(0, |h, k| h | color_bit[k - n]);
+    let d = |h: usize, from: (usize, usize), to: (usize, usize)| paths[h].distance(from, to);
+    let to_goal = |h: usize, from: (usize, usize)| goal.map_or(Some(0), |g| d(h, from, g));
+    let all_items = (1usize << n) - 1;
+
+    if stops.len() > EXACT_ROUTE_LIMIT {
+        // vecino más cercano, dulce o llave
+        let mut left: Vec<usize> = (0..stops.len()).collect();
+        let (mut total, mut at, mut mask) = (0, start, 0usize);
+        while mask & all_items != all_items || to_goal(held(mask), at).is_none() {
+            let Some((pos, cost)) = left
+                .iter()
+                .enumerate()
+                .filter_map(|(pos, &k)| d(held(mask), at, stops[k]).map(|cost| (pos, cost)))
+                .min_by_key(|&(_, cost)| cost)
+            else { break };
+            total += cost;
+            let k = left.swap_remove(pos);
+            at = stops[k];
+            mask |= 1 << k;
+        }
+        return total + to_goal(held(mask), at).unwrap_or(0);
+    }
+
+    // Held-Karp: best[mask][k] = ruta más corta que visita `mask` y termina en k
+    let m = stops.len();
+    let full = 1usize << m;
+    let mut best = vec![vec![usize::MAX; m]; full];
+    for k in 0..m {
+        if let Some(c) = d(0, start, stops[k]) { best[1 << k][k] = c; }
+    }
+    let mut answer = if n == 0 { to_goal(0, start).unwrap_or(usize::MAX) } else { usize::MAX };
+    for mask in 1..full {
+        let h = held(mask);
+        for k in 0..m {
+            let cost = best[mask][k];
+            if cost == usize::MAX || mask & (1 << k) == 0 { continue; }
+            if mask & all_items == all_items && let Some(c) = to_goal(h, stops[k]) {
+                answer = answer.min(cost + c);
+            }
+            for next in 0..m {
+                if mask & (1 << next) != 0 { continue; }
+                let Some(step) = d(h, stops[k], stops[next]) else { continue };
+                let nm = mask | (1 << next);
+                if cost + step < best[nm][next] { best[nm][next] = cost + step; }
+            }
+        }
+    }
+    if answer == usize::MAX { 0 } else { answer }
+}
+
+/// Casillas que se pueden pisar; `player`, `caster` y `pathfind` usan esta regla.
+/// '1'..'3' también cuentan porque `load_level` los convierte en espacio.
+/// Las puertas ('R', 'A', 'M') no: se vuelven espacio al abrirse.
+#[inline]
+pub fn is_walkable(c: char) -> bool {
+    matches!(c, ' ' | 'g' | 'b' | 'V' | 'B' | 'E' | 'p' | '1' | '2' | '3' | 'r' | 'a' | 'm')
 }
 
 pub fn find_char(maze: &Maze, target: char) -> Option<(usize, usize)> {
@@ -23,4 +356,90 @@ pub fn find_char(maze: &Maze, target: char) -> Option<(usize, usize)> {
         }
     }
     None
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::{Manifest, MANIFEST_FILE};
+
+    fn maze(text: &str) -> Maze {
+        text.lines().map(|l| l.chars().collect()).collect()
+    }
+
+    #[test]
+    fn shipped_levels_are_completable() {
+        let manifest = Manifest::load(MANIFEST_FILE).unwrap();
+        for spec in &manifest.levels {
+            let file = spec.maze.as_str();
+            let m = load_maze(file, spec.objective.needs_collectibles()).unwrap_or_else(|e| panic!("{}: {}", file, e));
+            let report = analyze(&m).unwrap();
+            assert!(report.is_ok(), "{}:\n{}", file, report);
+            assert!(report.route_length.is_some());
+        }
+    }
+
+    #[test]
+    fn only_reach_exit_levels_go_without_candies() {
+        let manifest = Manifest::load(MANIFEST_FILE).unwrap();
+        let exit_only = maze("+---+\n|p g|\n+---+");
+        // maze.txt es de recoger: con meta y sin dulces sigue sin valer
+        assert!(matches!(validate(&exit_only, manifest.needs_collectibles("maze.txt")), Err(MazeError::NoCollectibles)));
+        assert!(validate(&exit_only, manifest.needs_collectibles("./maze7.txt")).is_ok());
+        assert!(manifest.needs_collectibles("suelto.txt"));
+    }
+
+    #[test]
+    fn route_collects_everything_then_reaches_goal() {
+        let m = maze("+------+\n\
+                      |b p bg|\n\
+                      +------+");
+        // p -> b (2) -> b (4) -> g (1)
+        assert_eq!(analyze(&m).unwrap().route_length, Some(7));
+    }
+
+    #[test]
+    fn route_goes_for_the_key_before_crossing_its_door() {
+        // el dulce está a 4 pasos, pero la llave queda a 3 en sentido contrario
+        let m = maze("+--------+\n\
+                      |r  p R b|\n\
+                      +--------+");
+        assert_eq!(analyze(&m).unwrap().route_length, Some(10));
+    }
+
+    #[test]
+    fn reports_unreachable_items_and_pockets() {
+        let m = maze("+----+\n\
+                      |p b |\n\
+                      +----+\n\
+                      | V g|\n\
+                      +----+");
+        let report = analyze(&m).unwrap();
+        let tiles: Vec<char> = report.unreachable.iter().map(|p| p.tile).collect();
+        assert_eq!(tiles, vec!['V', 'g']);
+        assert_eq!(report.pockets, vec![Pocket { line: 4, column: 2, cells: 4 }]);
+        assert_eq!(report.route_length, None);
+
+        assert!(matches!(validate(&m, true), Err(MazeError::UnreachableGoal { line: 4, column: 5 })));
+    }
+
+    #[test]
+    fn structural_errors_carry_positions() {
+        assert!(matches!(
+            validate(&maze("+--+\n|pb|\n|  \n+--+"), true),
+            Err(MazeError::RaggedRow { line: 3, expected: 4, found: 3 })
+        ));
+        assert!(matches!(
+            validate(&maze("+--+\n|pb|\n|x |\n+--+"), true),
+            Err(MazeError::UnknownTile { line: 3, column: 2, tile: 'x' })
+        ));
+        assert!(matches!(
+            validate(&maze("+--+\n|pb|\n| p|\n+--+"), true),
+            Err(MazeError::MultipleSpawns { line: 3, column: 3, first_line: 2, first_column: 2 })
+        ));
+        assert!(matches!(validate(&maze("+--+\n| b|\n+--+"), true), Err(MazeError::NoSpawn)));
+        assert!(matches!(validate(&maze("+--+\n|p |\n+--+"), true), Err(MazeError::NoCollectibles)));
+        // un nivel de solo llegar a la salida no necesita dulces
+        assert!(validate(&maze("+---+\n|p g|\n+---+"), false).is_ok());
+    }
+}
@@ -0,0 +1,447 @@
+// mazegen.rs
+// Generador de laberintos en el mismo formato de texto que maze.txt: cada celda
+// ocupa tres caracteres de ancho (su borde izquierdo y dos de pasillo) y dos
+// filas (su pared de arriba y el pasillo), con '+' en las esquinas, "--" y '|'
+// como paredes.
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::maze::Maze;
+
+/// Generador pseudoaleatorio SplitMix64. Es propio para que una semilla dé el
+/// mismo laberinto en cualquier máquina y versión.
+#[derive(Debug, Clone)]
+pub struct Rng { state: u64 }
+
+impl Rng {
+    pub fn new(seed: u64) -> Self { Rng { state: seed } }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Entero en `0..n`; `n` debe ser mayor que cero.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Real en `0..1`.
+    pub fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for k in (1..items.len()).rev() {
+            items.swap(k, self.below(k + 1));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm { Backtracker, Prim, Kruskal, Wilson }
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 4] = [Algorithm::Backtracker, Algorithm::Prim, Algorithm::Kruskal, Algorithm::Wilson];
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Algorithm::Backtracker => "backtracker",
+            Algorithm::Prim => "prim",
+            Algorithm::Kruskal => "kruskal",
+            Algorithm::Wilson => "wilson",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Algorithm::ALL
+            .into_iter()
+            .find(|a| a.to_string() == s.to_ascii_lowercase())
+            .ok_or_else(|| format!("algoritmo desconocido \"{}\" (backtracker, prim, kruskal, wilson)", s))
+    }
+}
+
+/// Parámetros de generación. Las distancias se miden en celdas del laberinto
+/// (no en caracteres del archivo) siguiendo los pasillos.
+#[derive(Debug, Clone)]
+pub struct GenOptions {
+    /// Celdas a lo ancho y a lo alto.
+    pub width: usize,
+    pub height: usize,
+    pub algorithm: Algorithm,
+    pub seed: u64,
+    /// Fracción de callejones sin salida que se abren (0 = laberinto perfecto).
+    pub braid: f32,
+    pub collectibles: usize,
+    /// Dónde queda la meta, como fracción de la distancia máxima desde el inicio.
+    pub goal_distance: f32,
+    /// Distancia mínima entre el inicio y cada dulce.
+    pub min_item_distance: usize,
+    /// Distancia mínima deseada entre dulces; se relaja si no caben.
+    pub item_spacing: usize,
+}
+
+impl Default for GenOptions {
+    fn default() -> Self {
+        GenOptions {
+            width: 8,
+            height: 8,
+            algorithm: Algorithm::Backtracker,
+            seed: 0,
+            braid: 0.0,
+            collectibles: 4,
+            goal_distance: 1.0,
+            min_item_distance: 2,
+            item_spacing: 3,
+        }
+    }
+}
+
+/// Rejilla de celdas con los pasos abiertos hacia el este y hacia el sur.
+struct Grid {
+    width: usize,
+    height: usize,
+    east: Vec<bool>,
+    south: Vec<bool>,
+}
+
+impl Grid {
+    fn new(width: usize, height: usize) -> Self {
+        Grid { width, height, east: vec![false; width * height], south: vec![false; width * height] }
+    }
+
+    fn neighbours(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = (cell % self.width, cell / self.width);
+        [
+            (x > 0).then(|| cell - 1),
+            (x + 1 < self.width).then(|| cell + 1),
+            (y > 0).then(|| cell - self.width),
+            (y + 1 < self.height).then(|| cell + self.width),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    fn is_open(&self, a: usize, b: usize) -> bool {
+        let (lo, hi) = (a.min(b), a.max(b));
+        if hi == lo + 1 { self.east[lo] } else { self.south[lo] }
+    }
+
+    /// Quita la pared entre dos celdas vecinas.
+    fn open(&mut self, a: usize, b: usize) {
+        let (lo, hi) = (a.min(b), a.max(b));
+        if hi == lo + 1 { self.east[lo] = true; } else { self.south[lo] = true; }
+    }
+
+    fn passages(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        self.neighbours(cell).filter(move |&n| self.is_open(cell, n))
+    }
+
+    fn distances_from(&self, start: usize) -> Vec<usize> {
+        let mut dist = vec![usize::MAX; self.width * self.height];
+        let mut queue = VecDeque::from([start]);
+        dist[start] = 0;
+        while let Some(cell) = queue.pop_front() {
+            for n in self.passages(cell) {
+                if dist[n] == usize::MAX {
+                    dist[n] = dist[cell] + 1;
+                    queue.push_back(n);
+                }
+            }
+        }
+        dist
+    }
+}
+
+fn backtracker(grid: &mut Grid, rng: &mut Rng) {
+    let mut visited = vec![false; grid.width * grid.height];
+    let start = rng.below(visited.len());
+    let mut stack = vec![start];
+    visited[start] = true;
+
+    while let Some(&cell) = stack.last() {
+        let options: Vec<usize> = grid.neighbours(cell).filter(|&n| !visited[n]).collect();
+        if options.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let next = options[rng.below(options.len())];
+        grid.open(cell, next);
+        visited[next] = true;
+        stack.push(next);
+    }
+}
+
+fn prim(grid: &mut Grid, rng: &mut Rng) {
+    let mut in_maze = vec![false; grid.width * grid.height];
+    let mut in_frontier = vec![false; in_maze.len()];
+    let mut frontier = Vec::new();
+
+    let start = rng.below(in_maze.len());
+    in_maze[start] = true;
+    for n in grid.neighbours(start) {
+        in_frontier[n] = true;
+        frontier.push(n);
+    }
+
+    while !frontier.is_empty() {
+        let cell = frontier.swap_remove(rng.below(frontier.len()));
+        let links: Vec<usize> = grid.neighbours(cell).filter(|&n| in_maze[n]).collect();
+        grid.open(cell, links[rng.below(links.len())]);
+        in_maze[cell] = true;
+
+        let fresh: Vec<usize> = grid.neighbours(cell).filter(|&n| !in_maze[n] && !in_frontier[n]).collect();
+        for n in fresh {
+            in_frontier[n] = true;
+            frontier.push(n);
+        }
+    }
+}
+
+fn kruskal(grid: &mut Grid, rng: &mut Rng) {
+    let n = grid.width * grid.height;
+    let mut edges: Vec<(usize, usize)> = (0..n)
+        .flat_map(|c| grid.neighbours(c).filter(move |&m| m > c).map(move |m| (c, m)).collect::<Vec<_>>())
+        .collect();
+    rng.shuffle(&mut edges);
+
+    let mut parent: Vec<usize> = (0..n).collect();
+    fn root(parent: &mut [usize], mut c: usize) -> usize {
+        while parent[c] != c {
+            parent[c] = parent[parent[c]];
+            c = parent[c];
+        }
+        c
+    }
+
+    for (a, b) in edges {
+        let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+        if ra != rb {
+            parent[ra] = rb;
+            grid.open(a, b);
+        }
+    }
+}
+
+/// Caminatas aleatorias con borrado de ciclos: da laberintos uniformes.
+fn wilson(grid: &mut Grid, rng: &mut Rng) {
+    let n = grid.width * grid.height;
+    let mut in_maze = vec![false; n];
+    let mut next = vec![usize::MAX; n];
+    in_maze[rng.below(n)] = true;
+
+    let mut order: Vec<usize> = (0..n).collect();
+    rng.shuffle(&mut order);
+
+    for start in order {
+        if in_maze[start] { continue; }
+
+        // al volver a pisar una celda se sobrescribe su salida, eso borra el ciclo
+        let mut cell = start;
+        while !in_maze[cell] {
+            let options: Vec<usize> = grid.neighbours(cell).collect();
+            next[cell] = options[rng.below(options.len())];
+            cell = next[cell];
+        }
+
+        let mut cell = start;
+        while !in_maze[cell] {
+            in_maze[cell] = true;
+            grid.open(cell, next[cell]);
+            cell = next[cell];
+        }
+    }
+}
+
+/// Abre paredes en una parte de los callejones sin salida, creando ciclos.
+fn braid(grid: &mut Grid, rng: &mut Rng, amount: f32) {
+    let mut dead_ends: Vec<usize> = (0..grid.width * grid.height)
+        .filter(|&c| grid.passages(c).count() == 1)
+        .collect();
+    rng.shuffle(&mut dead_ends);
+
+    for cell in dead_ends {
+        if grid.passages(cell).count() != 1 || rng.unit() >= amount { continue; }
+        let closed: Vec<usize> = grid.neighbours(cell).filter(|&n| !grid.is_open(cell, n)).collect();
+        if closed.is_empty() { continue; }
+        // unir dos callejones a la vez cuando se puede
+        let paired: Vec<usize> = closed.iter().copied().filter(|&n| grid.passages(n).count() == 1).collect();
+        let pick = if paired.is_empty() { &closed } else { &paired };
+        grid.open(cell, pick[rng.below(pick.len())]);
+    }
+}
+
+/// Elige inicio, meta y dulces a las distancias pedidas.
+fn place_items(grid: &Grid, rng: &mut Rng, opts: &GenOptions) -> (usize, usize, Vec<usize>) {
+    let n = grid.width * grid.height;
+    let spawn = rng.below(n);
+    let dist = grid.distances_from(spawn);
+    let max = dist.iter().copied().max().unwrap_or(0);
+
+    let target = (max as f32 * opts.goal_distance.clamp(0.0, 1.0)).round() as usize;
+    let goal = (0..n)
+        .filter(|&c| c != spawn || n == 1)
+        .min_by_key(|&c| (dist[c].abs_diff(target), c))
+        .unwrap_or(spawn);
+
+    let mut candidates: Vec<usize> = (0..n).filter(|&c| c != spawn && c != goal).collect();
+    rng.shuffle(&mut candidates);
+
+    let mut items: Vec<usize> = Vec::new();
+    let mut item_dist: Vec<Vec<usize>> = Vec::new();
+    let mut spacing = opts.item_spacing;
+    let mut min_from_spawn = opts.min_item_distance;
+    while items.len() < opts.collectibles.min(candidates.len()) {
+        for &c in &candidates {
+            if items.len() >= opts.collectibles { break; }
+            if items.contains(&c) || dist[c] < min_from_spawn { continue; }
+            if item_dist.iter().any(|d| d[c] < spacing) { continue; }
+            items.push(c);
+            item_dist.push(grid.distances_from(c));
+        }
+        // si no caben con esas distancias se relajan poco a poco
+        if spacing == 0 && min_from_spawn == 0 { break; }
+        spacing = spacing.saturating_sub(1);
+        min_from_spawn = min_from_spawn.saturating_sub(1);
+    }
+
+    (spawn, goal, items)
+}
+
+/// Pasa la rejilla al formato de texto de los niveles.
+fn to_maze(grid: &Grid, spawn: usize, goal: usize, items: &[usize]) -> Maze {
+    let cols = grid.width * 3 + 1;
+    let mut maze: Maze = Vec::with_capacity(grid.height * 2 + 1);
+
+    for y in 0..grid.height {
+        let mut top = Vec::with_capacity(cols);
+        let mut mid = Vec::with_capacity(cols);
+        for x in 0..grid.width {
+            let cell = y * grid.width + x;
+            let up = y > 0 && grid.south[cell - grid.width];
+            let left = x > 0 && grid.east[cell - 1];
+            top.extend(['+', if up { ' ' } else { '-' }, if up { ' ' } else { '-' }]);
+            mid.extend([if left { ' ' } else { '|' }, ' ', ' ']);
+        }
+        top.push('+');
+        mid.push('|');
+        maze.push(top);
+        maze.push(mid);
+    }
+    let mut bottom = Vec::with_capacity(cols);
+    for _ in 0..grid.width { bottom.extend(['+', '-', '-']); }
+    bottom.push('+');
+    maze.push(bottom);
+
+    let mut put = |cell: usize, tile: char| {
+        let (x, y) = (cell % grid.width, cell / grid.width);
+        maze[y * 2 + 1][x * 3 + 1] = tile;
+    };
+    for &c in items { put(c, 'b'); }
+    put(goal, 'g');
+    put(spawn, 'p');
+    maze
+}
+
+/// Genera un laberinto jugable; con las mismas opciones siempre sale el mismo.
+pub fn generate(opts: &GenOptions) -> Maze {
+    let mut rng = Rng::new(opts.seed);
+    let mut grid = Grid::new(opts.width.max(1), opts.height.max(1));
+
+    match opts.algorithm {
+        Algorithm::Backtracker => backtracker(&mut grid, &mut rng),
+        Algorithm::Prim => prim(&mut grid, &mut rng),
+        Algorithm::Kruskal => kruskal(&mut grid, &mut rng),
+        Algorithm::Wilson => wilson(&mut grid, &mut rng),
+    }
+    if opts.braid > 0.0 { braid(&mut grid, &mut rng, opts.braid); }
+
+    let (spawn, goal, items) = place_items(&grid, &mut rng, opts);
+    to_maze(&grid, spawn, goal, &items)
+}
+
+pub fn maze_to_string(maze: &Maze) -> String {
+    let mut out = String::new();
+    for row in maze {
+        out.extend(row.iter());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{analyze, validate};
+
+    fn opts(algorithm: Algorithm, seed: u64) -> GenOptions {
+        GenOptions { width: 12, height: 9, algorithm, seed, ..GenOptions::default() }
+    }
+
+    /// Celdas abiertas entre sí en el texto; cuenta pasos, no caracteres.
+    fn open_passages(maze: &Maze) -> usize {
+        let mut n = 0;
+        for (j, row) in maze.iter().enumerate() {
+            for (i, &c) in row.iter().enumerate() {
+                if c != ' ' { continue; }
+                if j % 2 == 0 && i % 3 == 1 { n += 1; }
+                if j % 2 == 1 && i % 3 == 0 { n += 1; }
+            }
+        }
+        n
+    }
+
+    #[test]
+    fn every_algorithm_builds_a_perfect_playable_maze() {
+        for algorithm in Algorithm::ALL {
+            for seed in 0..5 {
+                let m = generate(&opts(algorithm, seed));
+                assert_eq!(m.len(), 9 * 2 + 1);
+                assert!(m.iter().all(|row| row.len() == 12 * 3 + 1));
+                validate(&m, true).unwrap_or_else(|e| panic!("{} {}: {}", algorithm, seed, e));
+                let report = analyze(&m).unwrap();
+                assert!(report.is_ok() && report.pockets.is_empty(), "{} {}:\n{}", algorithm, seed, report);
+                // árbol: todas las celdas conectadas con exactamente n-1 pasos
+                assert_eq!(open_passages(&m), 12 * 9 - 1, "{} {}", algorithm, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_maze() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(generate(&opts(algorithm, 42)), generate(&opts(algorithm, 42)));
+            assert_ne!(generate(&opts(algorithm, 42)), generate(&opts(algorithm, 43)));
+        }
+    }
+
+    #[test]
+    fn braiding_adds_loops_and_places_items() {
+        let o = GenOptions { braid: 1.0, collectibles: 6, ..opts(Algorithm::Backtracker, 7) };
+        let m = generate(&o);
+        validate(&m, true).unwrap();
+        assert!(open_passages(&m) > 12 * 9 - 1);
+
+        let count = |t: char| m.iter().flatten().filter(|&&c| c == t).count();
+        assert_eq!((count('p'), count('g'), count('b')), (1, 1, 6));
+    }
+
+    #[test]
+    fn algorithm_names_round_trip() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(algorithm.to_string().parse::<Algorithm>(), Ok(algorithm));
+        }
+        assert!("laberinto".parse::<Algorithm>().is_err());
+    }
+}
@@ -0,0 +1,237 @@
+// pathfind.rs
+// Caminos sobre la cuadrícula del laberinto, con la misma regla de paso que el
+// jugador y el caster (`maze::is_walkable`) y en 4 direcciones: camino más
+// corto (A*), campo de distancias (BFS) y campo de flujo. Los campos se guardan
+// por casilla de destino, así que muchos agentes pueden preguntar cada frame.
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::rc::Rc;
+
+use crate::maze::{Maze, is_walkable};
+
+/// Casilla como (columna, fila), desde 0.
+pub type Cell = (usize, usize);
+
+/// Campos guardados como máximo; al pasarse se vacía la caché.
+const MAX_CACHED: usize = 64;
+
+/// Pasos mínimos desde cada casilla hasta un destino.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceField {
+    width: usize,
+    dist: Vec<Option<u32>>,
+}
+
+impl DistanceField {
+    /// `None` si desde `cell` no se llega (o está fuera del laberinto).
+    pub fn get(&self, (i, j): Cell) -> Option<usize> {
+        if i >= self.width { return None; }
+        self.dist.get(j * self.width + i).copied().flatten().map(|d| d as usize)
+    }
+}
+
+/// Para cada casilla, la vecina por la que se llega antes al destino.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowField {
+    width: usize,
+    next: Vec<Option<Cell>>,
+}
+
+impl FlowField {
+    /// `None` en el destino y en las casillas desde las que no se llega.
+    pub fn next(&self, (i, j): Cell) -> Option<Cell> {
+        if i >= self.width { return None; }
+        self.next.get(j * self.width + i).copied().flatten()
+    }
+}
+
+/// Consultas de caminos sobre un laberinto, con caché de campos por destino.
+pub struct Pathfinder {
+    width: usize,
+    height: usize,
+    walkable: Vec<bool>,
+    distances: RefCell<HashMap<Cell, Rc<DistanceField>>>,
+    flows: RefCell<HashMap<Cell, Rc<FlowField>>>,
+}
+
+impl Pathfinder {
+    pub fn new(maze: &Maze) -> Pathfinder {
+        let width = maze.iter().map(Vec::len).max().unwrap_or(0);
+        let height = maze.len();
+        let mut walkable = vec![false; width * height];
+        for (j, row) in maze.iter().enumerate() {
+            for (i, &c) in row.iter().enumerate() {
+                walkable[j * width + i] = is_walkable(c);
+            }
+        }
+        Pathfinder {
+            width,
+            height,
+            walkable,
+            distances: RefCell::new(HashMap::new()),
+            flows: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_walkable(&self, (i, j): Cell) -> bool {
+        i < self.width && j < self.height && self.walkable[j * self.width + i]
+    }
+
+    /// Cambia una casilla (por ejemplo al abrir una puerta) y olvida los campos guardados.
+    pub fn set_walkable(&mut self, (i, j): Cell, walkable: bool) {
+        if i >= self.width || j >= self.height { return; }
+        self.walkable[j * self.width + i] = walkable;
+        self.distances.get_mut().clear();
+        self.flows.get_mut().clear();
+    }
+
+    /// Vecinas caminables en las 4 direcciones.
+    pub fn neighbours(&self, (i, j): Cell) -> impl Iterator<Item = Cell> + '_ {
+        [
+            (i.wrapping_sub(1), j),
+            (i + 1, j),
+            (i, j.wrapping_sub(1)),
+            (i, j + 1),
+        ]
+        .into_iter()
+        .filter(|&c| self.is_walkable(c))
+    }
+
+    /// Campo de distancias hasta `goal` (BFS); se calcula una vez por destino.
+    pub fn distance_field(&self, goal: Cell) -> Rc<DistanceField> {
+        if let Some(field) = self.distances.borrow().get(&goal) { return Rc::clone(field); }
+
+        let mut dist = vec![None; self.width * self.height];
+        if self.is_walkable(goal) {
+            dist[goal.1 * self.width + goal.0] = Some(0);
+            let mut queue = VecDeque::from([goal]);
+            while let Some(cell) = queue.pop_front() {
+                let here = dist[cell.1 * self.width + cell.0].unwrap_or(0);
+                for (ni, nj) in self.neighbours(cell) {
+                    let k = nj * self.width + ni;
+                    if dist[k].is_none() {
+                        dist[k] = Some(here + 1);
+                        queue.push_back((ni, nj));
+                    }
+                }
+            }
+        }
+
+        let field = Rc::new(DistanceField { width: self.width, dist });
+        let mut cache = self.distances.borrow_mut();
+        if cache.len() >= MAX_CACHED { cache.clear(); }
+        cache.insert(goal, Rc::clone(&field));
+        field
+    }
+
+    /// Campo de flujo hacia `goal`, sacado de su campo de distancias.
+    pub fn flow_field(&self, goal: Cell) -> Rc<FlowField> {
+        if let Some(field) = self.flows.borrow().get(&goal) { return Rc::clone(field); }
+
+        let dist = self.distance_field(goal);
+        let mut next = vec![None; self.width * self.height];
+        for j in 0..self.height {
+            for i in 0..self.width {
+                let Some(d) = dist.get((i, j)) else { continue };
+                if d == 0 { continue; }
+                next[j * self.width + i] = self.neighbours((i, j)).find(|&n| dist.get(n) == Some(d - 1));
+            }
+        }
+
+        let field = Rc::new(FlowField { width: self.width, next });
+        let mut cache = self.flows.borrow_mut();
+        if cache.len() >= MAX_CACHED { cache.clear(); }
+        cache.insert(goal, Rc::clone(&field));
+        field
+    }
+
+    /// Pasos entre dos casillas, usando el campo guardado de `to`.
+    pub fn distance(&self, from: Cell, to: Cell) -> Option<usize> {
+        self.distance_field(to).get(from)
+    }
+
+    /// Camino más corto de `from` a `to`, ambos incluidos (A* con distancia
+    /// Manhattan). Para consultas sueltas; no usa la caché.
+    pub fn shortest_path(&self, from: Cell, to: Cell) -> Option<Vec<Cell>> {
+        if !self.is_walkable(from) || !self.is_walkable(to) { return None; }
+        let h = |(i, j): Cell| i.abs_diff(to.0) + j.abs_diff(to.1);
+        let index = |(i, j): Cell| j * self.width + i;
+
+        let mut cost = vec![usize::MAX; self.width * self.height];
+        let mut came_from: Vec<Option<Cell>> = vec![None; self.width * self.height];
+        let mut open = BinaryHeap::from([Reverse((h(from), from))]);
+        cost[index(from)] = 0;
+
+        while let Some(Reverse((_, cell))) = open.pop() {
+            if cell == to {
+                let mut path = vec![to];
+                while let Some(prev) = came_from[index(*path.last()?)] { path.push(prev); }
+                path.reverse();
+                return Some(path);
+            }
+            let here = cost[index(cell)];
+            for n in self.neighbours(cell) {
+                if here + 1 < cost[index(n)] {
+                    cost[index(n)] = here + 1;
+                    came_from[index(n)] = Some(cell);
+                    open.push(Reverse((here + 1 + h(n), n)));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|r| r.chars().collect()).collect()
+    }
+
+    const ROWS: &[&str] = &[
+        "+-------+",
+        "|p   |  |",
+        "| +-+| ||",
+        "|   b   |",
+        "+---+---+",
+        "|  g    |",
+    ];
+
+    #[test]
+    fn path_distance_and_flow_agree() {
+        let paths = Pathfinder::new(&maze(ROWS));
+        let (from, to) = ((1, 1), (7, 1));
+
+        let path = paths.shortest_path(from, to).unwrap();
+        assert_eq!((path[0], *path.last().unwrap()), (from, to));
+        assert!(path.windows(2).all(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1) == 1));
+        assert_eq!(paths.distance(from, to), Some(path.len() - 1));
+
+        // siguiendo el flujo se llega en el mismo número de pasos
+        let flow = paths.flow_field(to);
+        let (mut at, mut steps) = (from, 0);
+        while let Some(n) = flow.next(at) { at = n; steps += 1; }
+        assert_eq!((at, steps), (to, path.len() - 1));
+
+        // la fila de abajo está separada por una pared
+        assert_eq!(paths.shortest_path(from, (3, 5)), None);
+        assert_eq!(paths.distance(from, (3, 5)), None);
+        assert_eq!(flow.next((3, 5)), None);
+    }
+
+    #[test]
+    fn fields_are_cached_until_the_maze_changes() {
+        let mut paths = Pathfinder::new(&maze(ROWS));
+        let a = paths.distance_field((7, 1));
+        assert!(Rc::ptr_eq(&a, &paths.distance_field((7, 1))));
+
+        // abrir el muro de la columna 5 acorta el camino
+        let before = paths.distance((1, 1), (7, 1)).unwrap();
+        paths.set_walkable((5, 1), true);
+        assert!(!Rc::ptr_eq(&a, &paths.distance_field((7, 1))));
+        assert!(paths.distance((1, 1), (7, 1)).unwrap() < before);
+    }
+}
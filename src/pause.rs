@@ -0,0 +1,148 @@
+// pause.rs
+// Menús sobre el juego: el de pausa (reanudar, reiniciar el nivel, ajustes,
+// volver a la selección de nivel o salir) y el de ajustes. Los ajustes se
+// cambian en caliente y duran hasta cerrar el juego; no se escriben en
+// settings.toml.
+use crate::input::{Action, Input};
+use crate::settings::{Effect, Settings};
+
+/// Lo que el menú de pausa le pide al juego.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseChoice { Resume, Restart, Settings, LevelSelect, Quit }
+
+/// Un paso de navegación, ya traducido de las acciones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nav { Up, Down, Select, Back }
+
+impl Nav {
+    /// Pausa y Atrás cuentan como volver.
+    pub fn read(input: &Input) -> Option<Nav> {
+        if input.pressed(Action::Pause) || input.pressed(Action::Back) { return Some(Nav::Back); }
+        if input.pressed(Action::Confirm) { return Some(Nav::Select); }
+        if input.pressed(Action::MoveForward) { return Some(Nav::Up); }
+        if input.pressed(Action::MoveBack) { return Some(Nav::Down); }
+        None
+    }
+}
+
+/// Lo que hay que dibujar de un menú en este frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuView {
+    pub title: &'static str,
+    pub items: Vec<String>,
+    pub selected: usize,
+}
+
+/// Mueve `selected` entre `rows` filas, dando la vuelta en los extremos.
+fn step(selected: &mut usize, rows: usize, nav: Nav) {
+    match nav {
+        Nav::Up => *selected = (*selected + rows - 1) % rows,
+        Nav::Down => *selected = (*selected + 1) % rows,
+        Nav::Select | Nav::Back => {}
+    }
+}
+
+const PAUSE_ITEMS: [(&str, PauseChoice); 5] = [
+    ("Continuar", PauseChoice::Resume),
+    ("Reiniciar nivel", PauseChoice::Restart),
+    ("Ajustes", PauseChoice::Settings),
+    ("Selección de nivel", PauseChoice::LevelSelect),
+    ("Salir", PauseChoice::Quit),
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct PauseMenu {
+    selected: usize,
+}
+
+impl PauseMenu {
+    pub fn navigate(&mut self, nav: Nav) -> Option<PauseChoice> {
+        match nav {
+            Nav::Back => Some(PauseChoice::Resume),
+            Nav::Select => Some(PAUSE_ITEMS[self.selected].1),
+            _ => { step(&mut self.selected, PAUSE_ITEMS.len(), nav); None }
+        }
+    }
+
+    pub fn view(&self) -> MenuView {
+        MenuView {
+            title: "Pausa",
+            items: PAUSE_ITEMS.iter().map(|(name, _)| name.to_string()).collect(),
+            selected: self.selected,
+        }
+    }
+}
+
+/// Filas del menú de ajustes; la última vuelve atrás.
+const SETTINGS_ROWS: usize = 6;
+
+#[derive(Debug, Clone, Default)]
+pub struct SettingsMenu {
+    selected: usize,
+}
+
+impl SettingsMenu {
+    /// Cambia el ajuste elegido; devuelve `true` cuando hay que cerrar el menú.
+    pub fn navigate(&mut self, nav: Nav, settings: &mut Settings) -> bool {
+        match nav {
+            Nav::Back => return true,
+            Nav::Select if self.selected + 1 == SETTINGS_ROWS => return true,
+            Nav::Select => toggle(settings, self.selected),
+            _ => step(&mut self.selected, SETTINGS_ROWS, nav),
+        }
+        false
+    }
+
+    pub fn view(&self, settings: &Settings) -> MenuView {
+        let cam = &settings.camera;
+        let onoff = |on: bool| if on { "sí" } else { "no" };
+        let items = vec![
+            format!("Balanceo al caminar: {}", onoff(cam.head_bob.enabled)),
+            format!("Suavizado del mouse: {}", onoff(cam.mouse_smoothing.enabled)),
+            format!("Giro al ir de lado: {}", onoff(cam.strafe_sway.enabled)),
+            format!("Hundimiento al caer: {}", onoff(cam.landing_dip.enabled)),
+            format!("Invertir mouse: {}", onoff(settings.input.invert_mouse_y)),
+            "Volver".to_string(),
+        ];
+        MenuView { title: "Ajustes", items, selected: self.selected }
+    }
+}
+
+/// Cambia el ajuste de la fila `row` del menú de ajustes.
+fn toggle(settings: &mut Settings, row: usize) {
+    let flip = |e: &mut Effect| e.enabled = !e.enabled;
+    let cam = &mut settings.camera;
+    match row {
+        0 => flip(&mut cam.head_bob),
+        1 => flip(&mut cam.mouse_smoothing),
+        2 => flip(&mut cam.strafe_sway),
+        3 => flip(&mut cam.landing_dip),
+        _ => settings.input.invert_mouse_y = !settings.input.invert_mouse_y,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn menus_navigate_and_toggle_settings() {
+        let mut menu = PauseMenu::default();
+        assert_eq!(menu.navigate(Nav::Up), None);
+        assert_eq!(menu.view().selected, PAUSE_ITEMS.len() - 1);
+        assert_eq!(menu.navigate(Nav::Select), Some(PauseChoice::Quit));
+        menu.navigate(Nav::Down);
+        menu.navigate(Nav::Down);
+        assert_eq!(menu.navigate(Nav::Select), Some(PauseChoice::Restart));
+        assert_eq!(menu.navigate(Nav::Back), Some(PauseChoice::Resume));
+
+        let mut settings = Settings::default();
+        let mut page = SettingsMenu::default();
+        assert!(!page.navigate(Nav::Select, &mut settings));
+        assert!(!settings.camera.head_bob.enabled);
+        assert!(page.view(&settings).items[0].ends_with("no"));
+        page.navigate(Nav::Up, &mut settings);
+        assert!(page.navigate(Nav::Select, &mut settings));
+        assert!(page.navigate(Nav::Back, &mut settings));
+    }
+}
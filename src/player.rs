@@ -1,17 +1,65 @@
 // player.rs
 use raylib::prelude::*;
 use std::f32::consts::PI;
-use crate::maze::Maze;
+use crate::camera::Camera;
+use crate::input::{Action, Input};
+use crate::maze::{Maze, is_walkable};
 
+#[derive(Debug, Clone, Copy)]
 pub struct Player {
     pub pos: Vector2,
     pub a: f32,
     pub fov: f32,
+    /// Mirada arriba (+) o abajo (-), en radianes.
+    pub pitch: f32,
+    /// Altura del salto sobre el suelo, en píxeles del mundo.
+    pub z: f32,
+    /// Velocidad vertical del salto.
+    pub vz: f32,
+    /// Cuánto está agachado, de 0 (de pie) a 1.
+    pub crouch: f32,
+}
+
+/// Lo que se movió el jugador en un frame, para los efectos de cámara.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Motion {
+    /// Píxeles recorridos de verdad (sin contar los choques).
+    pub distance: f32,
+    /// -1 hacia la izquierda (A), 1 hacia la derecha (D), 0 sin ir de lado.
+    pub strafe: f32,
+}
+
+/// Límite de la mirada vertical; más allá el y-shearing deforma demasiado.
+pub const MAX_PITCH: f32 = 0.6;
+const GRAVITY: f32 = 1200.0;
+/// Altura máxima del salto y cuánto baja el ojo agachado, en bloques.
+/// El ojo no debe pasar del alto de la pared (1 bloque).
+const JUMP_HEIGHT_FACTOR: f32 = 0.3;
+const CROUCH_FACTOR: f32 = 0.2;
+/// Velocidad con que se agacha o se levanta (fracción por segundo).
+const CROUCH_SPEED: f32 = 6.0;
+
+impl Player {
+    pub fn new(pos: Vector2, a: f32) -> Player {
+        Player { pos, a, fov: PI / 3.0, pitch: 0.0, z: 0.0, vz: 0.0, crouch: 0.0 }
+    }
+
+    /// Altura del ojo sobre el suelo; de pie está a media pared.
+    #[inline]
+    pub fn eye_height(&self, block_size: usize) -> f32 {
+        block_size as f32 * (0.5 - CROUCH_FACTOR * self.crouch) + self.z
+    }
+
+    /// Fila de pantalla del horizonte: baja al mirar arriba y sube al mirar abajo.
+    #[inline]
+    pub fn horizon(&self, height: u32, dist_proj_plane: f32) -> f32 {
+        height as f32 / 2.0 + self.pitch.tan() * dist_proj_plane
+    }
 }
 
 #[inline]
 fn is_wall(c: char) -> bool {
-    !(c == ' ' || c == 'g' || c == 'b' || c == 'V' || c == 'p')
+    !is_walkable(c)
 }
 
 #[inline]
@@ -43,34 +91,45 @@ fn can_stand(maze: &Maze, x: f32, y: f32, r: f32, block_size: usize) -> bool {
 }
 
 const PLAYER_RADIUS_FACTOR: f32 = 0.33;
-pub fn process_events(player: &mut Player, rl: &RaylibHandle, maze: &Maze, block_size: usize) {
-    let dt = rl.get_frame_time() as f32;
+/// Mueve al jugador según las acciones del frame. Los sticks dan valores
+/// intermedios, así que se puede girar y caminar despacio.
+pub fn process_events(player: &mut Player, input: &Input, dt: f32, maze: &Maze, block_size: usize, camera: &mut Camera) -> Motion {
+    let start = player.pos;
     const MOVE_SPEED: f32 = 220.0;
     const ROT_SPEED:  f32 = PI;
 
     let collision_radius: f32 = (block_size as f32) * PLAYER_RADIUS_FACTOR;
 
-    if rl.is_key_down(KeyboardKey::KEY_LEFT)  { player.a -= ROT_SPEED * dt; }
-    if rl.is_key_down(KeyboardKey::KEY_RIGHT) { player.a += ROT_SPEED * dt; }
+    player.a += input.axis(Action::TurnLeft, Action::TurnRight) * ROT_SPEED * dt;
+
+    // mouse: rotación horizontal con delta X, mirada vertical con delta Y
+    let md = camera.smooth_mouse(input.mouse_look(), dt);
+    player.a += md.x;
+    player.pitch -= md.y;
+    player.pitch += input.axis(Action::LookDown, Action::LookUp) * ROT_SPEED * 0.3 * dt;
+    player.pitch = player.pitch.clamp(-MAX_PITCH, MAX_PITCH);
 
-    // mouse: rotación horizontal con delta X
-    let md = rl.get_mouse_delta();
-    const MOUSE_SENS: f32 = 0.003; // sensibilidad
-    player.a += md.x * MOUSE_SENS;
+    let crouching = input.active(Action::Crouch);
+    let on_ground = player.z <= 0.0;
+    if input.pressed(Action::Jump) && on_ground && !crouching {
+        player.vz = (2.0 * GRAVITY * JUMP_HEIGHT_FACTOR * block_size as f32).sqrt();
+    }
+    update_vertical(player, crouching, dt);
 
     let dir = Vector2::new(player.a.cos(), player.a.sin());
+    let forward = input.axis(Action::MoveBack, Action::MoveForward);
+    let strafe = input.axis(Action::StrafeLeft, Action::StrafeRight);
 
-    let mut move_vec = Vector2::zero();
-    if rl.is_key_down(KeyboardKey::KEY_UP)    || rl.is_key_down(KeyboardKey::KEY_W) { move_vec += dir; }
-    if rl.is_key_down(KeyboardKey::KEY_DOWN)  || rl.is_key_down(KeyboardKey::KEY_S) { move_vec -= dir; }
-    if rl.is_key_down(KeyboardKey::KEY_A) { move_vec.x += -dir.y; move_vec.y +=  dir.x; }
-    if rl.is_key_down(KeyboardKey::KEY_D) { move_vec.x +=  dir.y; move_vec.y += -dir.x; }
+    let mut move_vec = dir * forward;
+    move_vec.x += dir.y * strafe;
+    move_vec.y -= dir.x * strafe;
 
     let len = (move_vec.x * move_vec.x + move_vec.y * move_vec.y).sqrt();
     if len > 0.0 {
         let ux = move_vec.x / len;
         let uy = move_vec.y / len;
-        let step = MOVE_SPEED * dt;
+        // en diagonal no se va más rápido; con el stick a medias, más lento
+        let step = MOVE_SPEED * dt * len.min(1.0);
 
         let nx = player.pos.x + ux * step;
         if can_stand(maze, nx, player.pos.y, collision_radius, block_size) {
@@ -97,4 +156,42 @@ pub fn process_events(player: &mut Player, rl: &RaylibHandle, maze: &Maze, block
 
     if player.a >= 2.0 * PI { player.a -= 2.0 * PI; }
     if player.a < 0.0       { player.a += 2.0 * PI; }
+
+    let (dx, dy) = (player.pos.x - start.x, player.pos.y - start.y);
+    Motion { distance: (dx * dx + dy * dy).sqrt(), strafe }
+}
+
+/// Aplica gravedad al salto y acerca `crouch` a lo que pide la tecla.
+fn update_vertical(player: &mut Player, crouching: bool, dt: f32) {
+    if player.z > 0.0 || player.vz > 0.0 {
+        player.vz -= GRAVITY * dt;
+        player.z += player.vz * dt;
+        if player.z <= 0.0 { player.z = 0.0; player.vz = 0.0; }
+    }
+    let target = if crouching { 1.0 } else { 0.0 };
+    let step = CROUCH_SPEED * dt;
+    player.crouch += (target - player.crouch).clamp(-step, step);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jump_peaks_below_the_wall_top_and_lands() {
+        let bs = 64;
+        let mut p = Player::new(Vector2::zero(), 0.0);
+        p.vz = (2.0 * GRAVITY * JUMP_HEIGHT_FACTOR * bs as f32).sqrt();
+        let mut peak: f32 = 0.0;
+        for _ in 0..120 {
+            update_vertical(&mut p, false, 1.0 / 60.0);
+            peak = peak.max(p.eye_height(bs));
+        }
+        assert!(peak > bs as f32 * 0.75 && peak < bs as f32, "pico {}", peak);
+        assert_eq!((p.z, p.vz), (0.0, 0.0));
+
+        for _ in 0..60 { update_vertical(&mut p, true, 1.0 / 60.0); }
+        assert_eq!(p.crouch, 1.0);
+        assert!(p.eye_height(bs) < bs as f32 * 0.5);
+    }
 }
\ No newline at end of file
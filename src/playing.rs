@@ -0,0 +1,346 @@
+// playing.rs
+// La escena del juego: mover al jugador, recoger dulces, pilas y llaves, abrir
+// puertas, los enemigos, la pista, el reloj del nivel y dibujar el mundo con su HUD.
+use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::display::{Hud, draw_hud, draw_status};
+use crate::door::{Doors, color_name, is_key};
+use crate::endless::Level;
+use crate::enemy::{ENEMY_TILE, Enemy, EnemyConfig};
+use crate::flashlight::Flashlight;
+use crate::hint::{Hint, Refusal};
+use crate::input::Action;
+use crate::lightmap::LightMap;
+use crate::levels::Objective;
+use crate::maze::{Maze, find_char};
+use crate::pathfind::Pathfinder;
+use crate::player::{Player, process_events};
+use crate::render::{Scenery, render_frame};
+use crate::scene::{Game, Scene, Transition};
+use crate::screens::{LoadError, Lost, Paused, Won};
+use crate::sprite::Sprite;
+use crate::{BLOCK_SIZE, SCREEN_H, SCREEN_W, Stage, load_level};
+
+/// Estado del nivel. Los instantes (`level_deadline`, `msg_until`, los de la
+/// pista y la linterna) van en reloj de juego, el de la ventana sin las pausas.
+struct GameState {
+    level: Level,
+    level_deadline: f64,
+    paused: bool,
+    msg_text: Option<String>,
+    msg_until: f64,
+    pause_started: f64,
+    /// Segundos de ventana pasados en pausa.
+    paused_total: f64,
+}
+
+impl GameState {
+    /// Reloj de juego en el instante `now` de la ventana.
+    fn clock(&self, now: f64) -> f64 { now - self.paused_total }
+
+    /// Detiene el reloj de juego mientras hay otra escena encima.
+    fn pause(&mut self, now: f64) {
+        if self.paused { return; }
+        self.paused = true;
+        self.pause_started = now;
+    }
+
+    /// Descuenta del reloj de juego el tiempo que estuvo en pausa.
+    fn resume(&mut self, now: f64) {
+        if !self.paused { return; }
+        self.paused_total += now - self.pause_started;
+        self.paused = false;
+    }
+}
+
+fn player_cell(player: &Player, block_size: usize) -> (usize, usize) {
+    ((player.pos.x as usize) / block_size, (player.pos.y as usize) / block_size)
+}
+
+pub struct Playing {
+    stage: Stage,
+    maze: Maze,
+    paths: Pathfinder,
+    doors: Doors,
+    sprites: Vec<Sprite>,
+    player: Player,
+    state: GameState,
+    scenery: Scenery,
+    flashlight: Option<Flashlight>,
+    enemies: Vec<Enemy>,
+    enemy_config: EnemyConfig,
+    hint: Hint,
+    objective: Objective,
+    /// Casilla de la salida y si ya deja pasar, si el objetivo la usa.
+    exit: Option<((usize, usize), bool)>,
+    hud: Hud,
+}
+
+/// Segundos que se ve el aviso de que te alcanzó un enemigo.
+const CAUGHT_MSG_SECS: f64 = 1.5;
+/// Segundos que se ve la respuesta al pedir una pista.
+const HINT_MSG_SECS: f64 = 1.5;
+/// Segundos que se ven los avisos de llaves y puertas.
+const DOOR_MSG_SECS: f64 = 1.5;
+/// Segundos que se ve el aviso de que se abrió la salida.
+const EXIT_MSG_SECS: f64 = 2.0;
+
+impl Playing {
+    /// Carga `stage` y prepara su aspecto; si el archivo tiene errores, la
+    /// escena que sigue es la pantalla de error.
+    pub fn start(stage: Stage, rl: &mut RaylibHandle, game: &mut Game) -> Box<dyn Scene> {
+        let levels = &game.manifest.levels;
+        let loaded = match load_level(stage, levels, BLOCK_SIZE, rl) {
+            Ok(loaded) => loaded,
+            Err(e) => return Box::new(LoadError::new(&stage.file(levels), &e)),
+        };
+
+        let look = stage.look(levels.len());
+        let spec = &levels[look];
+        if game.applied_look != Some(look) {
+            game.texman.use_wall_set(game.manifest.wall_set(spec));
+            game.texman.use_sky(spec.sky_image.as_deref());
+            game.sound.set_music(&spec.music);
+            game.applied_look = Some(look);
+        }
+
+        let tint = spec.sky_tint.map_or(Color::WHITE, |t| t.0);
+        let mut scenery = Scenery::new(spec.sky.0, spec.floor.0, tint, &loaded.maze, &game.texman);
+        scenery.lighting = spec.lighting();
+        // las luces y la linterna están puestas para el laberinto del
+        // manifiesto; los generados no tienen pilas
+        let flashlight = match stage {
+            Stage::Manifest(_) => {
+                if let Some((lights, ambient)) = spec.point_lights() {
+                    scenery.light_map = Some(LightMap::bake(&loaded.maze, &lights, ambient, BLOCK_SIZE));
+                }
+                spec.flashlight(BLOCK_SIZE)
+            }
+            Stage::Endless { .. } => None,
+        };
+
+        let (enemies, sprites): (Vec<Sprite>, Vec<Sprite>) =
+            loaded.sprites.into_iter().partition(|s| s.kind == ENEMY_TILE);
+        let enemies = enemies.iter().map(|s| Enemy::from_sprite(s, BLOCK_SIZE)).collect();
+        let enemy_config = spec.enemy_config(BLOCK_SIZE);
+
+        game.camera.reset();
+        let state = GameState {
+            level: loaded.level,
+            level_deadline: loaded.deadline,
+            paused: false,
+            msg_text: None,
+            msg_until: 0.0,
+            pause_started: 0.0,
+            paused_total: 0.0,
+        };
+        let caption = match stage {
+            Stage::Endless { seed, .. } => Some(format!("Nivel {} - semilla {}", state.level.number, seed)),
+            Stage::Manifest(_) => None,
+        };
+        let hud = Hud {
+            candies_collected: 0,
+            candies_total: state.level.candies_needed,
+            remaining_secs: state.level.duration_secs,
+            caption,
+            battery: flashlight.map(|torch| torch.charge()),
+        };
+        let objective = loaded.objective;
+        let exit = find_char(&loaded.maze, 'g')
+            .filter(|_| objective.needs_exit())
+            .map(|cell| (cell, objective.exit_open(0, state.level.candies_needed)));
+        let mut playing = Playing {
+            stage,
+            paths: Pathfinder::new(&loaded.maze),
+            doors: Doors::from_maze(&loaded.maze),
+            maze: loaded.maze,
+            sprites,
+            player: Player::new(loaded.spawn, -PI / 2.0),
+            state,
+            scenery,
+            flashlight,
+            enemies,
+            enemy_config,
+            hint: Hint::new(spec.hint_config()),
+            objective,
+            exit,
+            hud,
+        };
+        // el primer frame se dibuja antes del primer `update`
+        let now = playing.state.clock(rl.get_time());
+        playing.render(rl, game, now);
+        Box::new(playing)
+    }
+
+    fn collected(&self) -> u32 {
+        self.sprites.iter().filter(|s| s.collected && s.is_candy()).count() as u32
+    }
+
+    /// Pide una pista hacia el dulce más cercano y avisa del resultado.
+    fn ask_hint(&mut self, now: f64, here: (usize, usize)) {
+        let targets = self.sprites.iter()
+            .filter(|s| !s.collected && s.is_candy())
+            .map(|s| ((s.x as usize) / BLOCK_SIZE, (s.y as usize) / BLOCK_SIZE));
+        let text = match self.hint.request(now, &self.paths, here, targets) {
+            Ok(penalty) => {
+                self.state.level_deadline -= penalty as f64;
+                format!("Pista: -{} s", penalty.round())
+            }
+            Err(Refusal::Cooldown(secs)) => format!("Otra pista en {} s", secs.ceil()),
+            Err(Refusal::Disabled) => "Este nivel no tiene pistas".to_string(),
+            Err(Refusal::NoTarget) => "No hay dulces a tu alcance".to_string(),
+        };
+        self.state.msg_text = Some(text);
+        self.state.msg_until = now + HINT_MSG_SECS;
+    }
+
+    /// Dibuja el mundo en el framebuffer y lo sube a la ventana.
+    fn render(&mut self, rl: &mut RaylibHandle, game: &mut Game, now: f64) {
+        self.scenery.flashlight = self.flashlight.map(|torch| torch.beam(now));
+        self.scenery.minimap = game.show_map;
+        self.scenery.breadcrumbs = self.hint.trail().cloned();
+        self.scenery.doors.clone_from(&self.doors);
+        self.scenery.exit = self.exit;
+
+        game.framebuffer.clear();
+        let view = game.camera.view(&self.player, BLOCK_SIZE);
+        let mut sprites = self.sprites.clone();
+        sprites.extend(self.enemies.iter().map(Enemy::sprite));
+        render_frame(&mut game.framebuffer, &self.maze, BLOCK_SIZE, &view, &sprites, &mut game.depth_buffer, &game.texman, &self.scenery);
+        game.screen.upload(rl, game.thread, &game.framebuffer);
+
+        self.hud.candies_collected = self.collected();
+        self.hud.remaining_secs = (self.state.level_deadline - now).ceil() as i32;
+        self.hud.battery = self.flashlight.map(|torch| torch.charge());
+    }
+}
+
+impl Scene for Playing {
+    fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        let now = self.state.clock(rl.get_time());
+        if game.input.pressed(Action::Map) { game.show_map = !game.show_map; }
+
+        if game.input.pressed(Action::Pause) {
+            return Transition::Push(Box::new(Paused::new(self.stage)));
+        }
+
+        if now >= self.state.msg_until { self.state.msg_text = None; }
+        let dt = rl.get_frame_time();
+        let motion = process_events(&mut self.player, &game.input, dt, &self.maze, BLOCK_SIZE, &mut game.camera);
+        if let Some(foot) = game.camera.update(&self.player, motion, BLOCK_SIZE, dt) {
+            game.sound.play_step(foot);
+        }
+
+        let (ci, cj) = player_cell(&self.player, BLOCK_SIZE);
+        for s in &mut self.sprites {
+            let si = (s.x as usize) / BLOCK_SIZE;
+            let sj = (s.y as usize) / BLOCK_SIZE;
+            if !s.collected && si == ci && sj == cj {
+                s.collected = true;
+                game.sound.play_piece();
+                if s.kind == 'B' && let Some(torch) = &mut self.flashlight { torch.recharge_battery(); }
+                if is_key(s.kind) {
+                    self.doors.give_key(s.kind);
+                    self.state.msg_text = Some(format!("Tienes la llave {}", color_name(s.kind)));
+                    self.state.msg_until = now + DOOR_MSG_SECS;
+                }
+            }
+        }
+
+        // las puertas abiertas del todo pasan a ser pasillo
+        for (i, j) in self.doors.update(self.player.pos, BLOCK_SIZE, dt) {
+            self.maze[j][i] = ' ';
+            self.paths.set_walkable((i, j), true);
+        }
+        if self.state.msg_text.is_none() && let Some(door) = self.doors.locked_near(self.player.pos, BLOCK_SIZE) {
+            self.state.msg_text = Some(format!("Necesitas la llave {}", color_name(door)));
+            self.state.msg_until = now + DOOR_MSG_SECS;
+        }
+        if let Some(torch) = &mut self.flashlight { torch.drain(dt); }
+
+        if game.input.pressed(Action::Hint) { self.ask_hint(now, (ci, cj)); }
+        self.hint.update(now, (ci, cj));
+
+        for enemy in &mut self.enemies {
+            if enemy.update(&self.maze, &self.doors, &self.paths, self.player.pos, &self.enemy_config, BLOCK_SIZE, dt) {
+                let penalty = self.enemy_config.penalty;
+                self.state.level_deadline -= penalty as f64;
+                self.state.msg_text = Some(format!("¡Te atrapó un osito! -{} s", penalty.round()));
+                self.state.msg_until = now + CAUGHT_MSG_SECS;
+            }
+        }
+
+        let needed = self.state.level.candies_needed;
+        let collected = self.collected();
+        let remaining = (self.state.level_deadline - now).ceil() as i32;
+
+        if let Some((_, open)) = &mut self.exit && !*open && self.objective.exit_open(collected, needed) {
+            *open = true;
+            self.state.msg_text = Some("¡La salida está abierta!".to_string());
+            self.state.msg_until = now + EXIT_MSG_SECS;
+        }
+        let on_exit = self.exit.is_some_and(|(cell, _)| cell == (ci, cj));
+
+        if self.objective.complete(collected, needed, on_exit) && remaining >= 0 {
+            let next = match self.stage {
+                Stage::Manifest(idx) if idx + 1 >= game.manifest.levels.len() => None,
+                Stage::Manifest(idx) => Some(Stage::Manifest(idx + 1)),
+                Stage::Endless { seed, number } => Some(Stage::Endless { seed, number: number + 1 }),
+            };
+            return Transition::Switch(Box::new(Won { next }));
+        } else if remaining < 0 {
+            // en el modo infinito se repite el mismo laberinto
+            return Transition::Switch(Box::new(Lost { stage: self.stage }));
+        }
+
+        self.render(rl, game, now);
+        Transition::Stay
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle<'_>, game: &Game) {
+        game.screen.draw(d);
+        draw_hud(d, SCREEN_W as i32, &self.hud);
+        if let Some(text) = &self.state.msg_text {
+            draw_status(d, SCREEN_W as i32, SCREEN_H as i32, text);
+        }
+    }
+
+    fn captures_mouse(&self) -> bool { true }
+
+    fn pause(&mut self, now: f64) { self.state.pause(now); }
+
+    fn resume(&mut self, now: f64) { self.state.resume(now); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hint::HintConfig;
+
+    #[test]
+    fn paused_time_does_not_reach_the_game_clock() {
+        let level = Level { number: 1, candies_needed: 3, duration_secs: 60 };
+        let mut state = GameState {
+            level,
+            level_deadline: 60.0,
+            paused: false,
+            msg_text: None,
+            msg_until: 0.0,
+            pause_started: 0.0,
+            paused_total: 0.0,
+        };
+        let m: Maze = ["+---+", "|   |", "+---+"].iter().map(|r| r.chars().collect()).collect();
+        let paths = Pathfinder::new(&m);
+        let mut hint = Hint::new(HintConfig::default());
+        hint.request(state.clock(5.0), &paths, (1, 1), [(3, 1)]).unwrap();
+
+        state.pause(10.0);
+        state.pause(12.0);
+        state.resume(25.0);
+        assert_eq!(state.clock(30.0), 15.0);
+        // la pista pedida antes de la pausa sigue en espera al volver
+        assert!(matches!(hint.request(state.clock(30.0), &paths, (1, 1), [(3, 1)]), Err(Refusal::Cooldown(_))));
+        assert_eq!(state.level_deadline, 60.0);
+    }
+}
@@ -0,0 +1,417 @@
+use raylib::prelude::*;
+use std::f32::consts::TAU;
+
+use crate::framebuffer::{Column, Framebuffer};
+use crate::caster::cast_ray;
+use crate::door::{Doors, is_door, map_color};
+use crate::enemy::ENEMY_TILE;
+use crate::flashlight::Beam;
+use crate::hint::Breadcrumbs;
+use crate::lighting::Lighting;
+use crate::lightmap::LightMap;
+use crate::texture::{CEILING, FLOOR, FLOOR_ALT, SKY, TextureManager};
+use crate::maze::Maze;
+use crate::player::Player;
+use crate::sprite::{Sprite, draw_sprite};
+
+/// Cielo y suelo de un nivel: colores lisos y, si hay, texturas por casilla.
+#[derive(Clone)]
+pub struct Scenery {
+    pub sky: Color,
+    pub floor: Color,
+    pub surfaces: Option<Surfaces>,
+    /// Tinte del panorama del cielo; `None` si no hay panorama cargado.
+    pub skybox: Option<Color>,
+    pub lighting: Lighting,
+    /// Luces horneadas del nivel; sin mapa todo queda a luz 1.
+    pub light_map: Option<LightMap>,
+    /// Linterna del jugador en este frame; fuera del cono queda casi negro.
+    pub flashlight: Option<Beam>,
+    /// Si se dibuja el minimapa encima.
+    pub minimap: bool,
+    /// Estela de la pista, si hay una pedida.
+    pub breadcrumbs: Option<Breadcrumbs>,
+    /// Cuánto se ha abierto cada puerta en este frame.
+    pub doors: Doors,
+    /// Casilla de la salida y si ya está abierta, en los niveles que la usan.
+    pub exit: Option<((usize, usize), bool)>,
+}
+
+/// Color de las migas de la pista; brillan igual a oscuras.
+const CRUMB_COLOR: Color = Color::new(255, 230, 120, 255);
+/// Radio de cada miga, en bloques.
+const CRUMB_RADIUS: f32 = 0.15;
+/// Marco de la salida en el suelo: rojo mientras está cerrada, verde al abrirse.
+const EXIT_LOCKED: Color = Color::new(200, 40, 50, 255);
+const EXIT_OPEN: Color = Color::new(80, 230, 120, 255);
+/// Ancho del marco, en bloques.
+const EXIT_BORDER: f32 = 0.12;
+
+impl Default for Scenery {
+    fn default() -> Self {
+        Scenery {
+            sky: Color::new(0xC7, 0xD9, 0xDD, 255),
+            floor: Color::new(255, 170, 170, 255),
+            surfaces: None,
+            skybox: None,
+            lighting: Lighting::default(),
+            light_map: None,
+            flashlight: None,
+            minimap: true,
+            breadcrumbs: None,
+            doors: Doors::default(),
+            exit: None,
+        }
+    }
+}
+
+impl Scenery {
+    /// Fondo de un nivel con las texturas de suelo, techo y cielo que tenga `texman`.
+    pub fn new(sky: Color, floor: Color, sky_tint: Color, maze: &Maze, texman: &TextureManager) -> Scenery {
+        Scenery {
+            sky,
+            floor,
+            surfaces: Surfaces::for_maze(maze, texman),
+            skybox: texman.has(SKY).then_some(sky_tint),
+            lighting: Lighting::default(),
+            light_map: None,
+            flashlight: None,
+            minimap: true,
+            breadcrumbs: None,
+            doors: Doors::from_maze(maze),
+            exit: None,
+        }
+    }
+
+    /// Suma el brillo de la miga de la pista que haya en el punto (x, y) del suelo.
+    #[inline]
+    fn crumb(&self, c: Color, x: f32, y: f32, block_size: usize) -> Color {
+        let Some(trail) = &self.breadcrumbs else { return c };
+        let (gx, gy) = (x / block_size as f32, y / block_size as f32);
+        if gx < 0.0 || gy < 0.0 || !trail.contains((gx as usize, gy as usize)) { return c; }
+        let r = (gx.fract() - 0.5).hypot(gy.fract() - 0.5);
+        if r >= CRUMB_RADIUS { return c; }
+        let t = 1.0 - r / CRUMB_RADIUS;
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+        Color::new(mix(c.r, CRUMB_COLOR.r), mix(c.g, CRUMB_COLOR.g), mix(c.b, CRUMB_COLOR.b), c.a)
+    }
+
+    /// Pinta la salida en el punto (x, y) del suelo: un marco y, mientras está
+    /// cerrada, rayas cruzadas. Brilla igual a oscuras, como las migas.
+    #[inline]
+    fn exit_pad(&self, c: Color, x: f32, y: f32, block_size: usize) -> Color {
+        let Some(((ei, ej), open)) = self.exit else { return c };
+        let (gx, gy) = (x / block_size as f32, y / block_size as f32);
+        if gx < 0.0 || gy < 0.0 || (gx as usize, gy as usize) != (ei, ej) { return c; }
+        let (fx, fy) = (gx.fract(), gy.fract());
+        let edge = fx.min(fy).min(1.0 - fx).min(1.0 - fy);
+        let (tint, t) = match (open, edge < EXIT_BORDER) {
+            (true, true) => (EXIT_OPEN, 0.85),
+            (true, false) => (EXIT_OPEN, 0.3),
+            (false, true) => (EXIT_LOCKED, 0.85),
+            (false, false) if ((fx + fy) * 3.0).fract() < 0.3 => (EXIT_LOCKED, 0.6),
+            (false, false) => return c,
+        };
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+        Color::new(mix(c.r, tint.r), mix(c.g, tint.g), mix(c.b, tint.b), c.a)
+    }
+
+    /// Color `c` bajo las luces del nivel en el punto (x, y) del mundo.
+    #[inline]
+    pub fn lit(&self, c: Color, x: f32, y: f32) -> Color {
+        match &self.light_map {
+            Some(map) => LightMap::modulate(c, map.sample(x, y)),
+            None => c,
+        }
+    }
+
+    /// Color `c` bajo la linterna, a `distance` del jugador y a (dx, dy)
+    /// píxeles del centro de la pantalla, con `proj` la distancia al plano.
+    #[inline]
+    pub fn torch(&self, c: Color, distance: f32, dx: f32, dy: f32, proj: f32) -> Color {
+        match &self.flashlight {
+            Some(beam) => beam.apply(c, distance, (dx.hypot(dy) / proj).atan()),
+            None => c,
+        }
+    }
+}
+
+/// Textura de suelo y techo de cada casilla, como clave de `TextureManager`.
+/// `None` en una casilla deja el color liso.
+#[derive(Clone)]
+pub struct Surfaces {
+    width: usize,
+    floor: Vec<Option<char>>,
+    ceiling: Vec<Option<char>>,
+}
+
+impl Surfaces {
+    /// Baldosas alternas en el suelo, la textura de meta bajo 'g' y el mismo
+    /// techo en todas partes. `None` si no hay texturas de suelo ni de techo.
+    pub fn for_maze(maze: &Maze, texman: &TextureManager) -> Option<Surfaces> {
+        if !texman.has(FLOOR) && !texman.has(CEILING) { return None; }
+        let width = maze.first().map_or(0, Vec::len);
+        let pick = |ch: char| texman.has(ch).then_some(ch);
+
+        let mut floor = Vec::with_capacity(width * maze.len());
+        let mut ceiling = Vec::with_capacity(width * maze.len());
+        for (j, row) in maze.iter().enumerate() {
+            for (i, &c) in row.iter().enumerate().take(width) {
+                let tile = match c {
+                    'g' => pick('g'),
+                    _ if (i + j) % 2 == 1 => pick(FLOOR_ALT).or(pick(FLOOR)),
+                    _ => pick(FLOOR),
+                };
+                floor.push(tile);
+                ceiling.push(pick(CEILING));
+            }
+        }
+        Some(Surfaces { width, floor, ceiling })
+    }
+
+    fn at(tiles: &[Option<char>], width: usize, i: f32, j: f32) -> Option<char> {
+        if i < 0.0 || j < 0.0 || i as usize >= width { return None; }
+        tiles.get(j as usize * width + i as usize).copied().flatten()
+    }
+}
+
+pub fn render_world(
+    framebuffer: &mut Framebuffer,
+    maze: &Maze,
+    block_size: usize,
+    player: &Player,
+    depth_buffer: &mut [f32],
+    texman: &TextureManager,
+    scenery: &Scenery,
+) {
+    let num_rays = framebuffer.width as usize;
+    let height = framebuffer.height;
+
+    assert_eq!(depth_buffer.len(), num_rays);
+
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = num_rays.div_ceil(workers).max(1);
+
+    std::thread::scope(|s| {
+        for (strip, depths) in framebuffer.strips_mut(chunk).zip(depth_buffer.chunks_mut(chunk)) {
+            s.spawn(move || {
+                for (mut col, depth) in strip.zip(depths) {
+                    *depth = render_column(&mut col, num_rays, height, maze, block_size, player, texman, scenery);
+                }
+            });
+        }
+    });
+}
+
+/// Lanza el rayo de una columna, la pinta y devuelve su profundidad corregida.
+#[allow(clippy::too_many_arguments)]
+fn render_column(
+    col: &mut Column,
+    num_rays: usize,
+    height: u32,
+    maze: &Maze,
+    block_size: usize,
+    player: &Player,
+    texman: &TextureManager,
+    scenery: &Scenery,
+) -> f32 {
+    let hw = num_rays as f32 / 2.0;
+    let hh = height as f32 / 2.0;
+    let dist_proj_plane = hw / (player.fov * 0.5).tan();
+
+    let t = col.x as f32 / num_rays as f32;
+    let a = player.a - (player.fov * 0.5) + (player.fov * t);
+
+    let hit = cast_ray(maze, player, a, block_size, &scenery.doors);
+    let corrected = (hit.distance * (a - player.a).cos()).max(1.0);
+
+    // y-shearing: la mirada vertical mueve el horizonte y la altura del ojo
+    // reparte la pared por encima y por debajo de él
+    let horizon = player.horizon(height, dist_proj_plane);
+    let eye = player.eye_height(block_size);
+    let wall_top = horizon - (block_size as f32 - eye) * dist_proj_plane / corrected;
+    let wall_bot = horizon + eye * dist_proj_plane / corrected;
+    let stake_h = wall_bot - wall_top;
+    let top = wall_top.clamp(0.0, height as f32) as u32;
+    let end = ((wall_bot.min(height as f32 - 1.0).floor() + 1.0).clamp(0.0, height as f32) as u32).max(top);
+
+    let u = hit.tex_u;
+
+    let ch = match hit.impact { '+' | '-' | '|' | 'g' => hit.impact, c if is_door(c) => c, _ => '#' };
+
+    // la luz de la pared es la de la casilla que tiene delante
+    let light = scenery.light_map.as_ref().map(|m| m.sample(hit.hit_x - a.cos(), hit.hit_y - a.sin()));
+
+    let dx = col.x as f32 + 0.5 - hw;
+    for y in top..end {
+        let v = ((y as f32 - wall_top) / stake_h).clamp(0.0, 0.999);
+        let mut color = texman.sample_uv(ch, u, v);
+        if let Some(light) = light { color = LightMap::modulate(color, light); }
+        color = scenery.torch(color, hit.distance, dx, y as f32 + 0.5 - hh, dist_proj_plane);
+        col.set(y, scenery.lighting.apply(color, corrected, Some(hit.face)));
+    }
+
+    let rows = Rows { top, end, height, half_width: hw, horizon, eye, dist_proj_plane };
+    cast_floor_and_ceiling(col, &rows, a, block_size, player, texman, scenery);
+
+    corrected
+}
+
+/// Color del cielo en la fila `y` de un rayo con ángulo `a`. El panorama da
+/// una vuelta completa cada 2π y cubre media pantalla hasta el horizonte;
+/// más arriba se repite su fila superior.
+#[inline]
+fn sky_color(scenery: &Scenery, texman: &TextureManager, a: f32, y: u32, hh: f32, horizon: f32) -> Color {
+    let Some(tint) = scenery.skybox else { return scenery.lighting.sky(scenery.sky) };
+    let u = (a / TAU).rem_euclid(1.0);
+    let v = 1.0 - ((y as f32 + 0.5 - (horizon - hh)) / hh).clamp(0.001, 1.0);
+    let c = texman.sample_uv(SKY, u, v);
+    let mul = |c: u8, t: u8| ((c as u16 * t as u16) / 255) as u8;
+    scenery.lighting.sky(Color::new(mul(c.r, tint.r), mul(c.g, tint.g), mul(c.b, tint.b), 255))
+}
+
+/// Filas de una columna: la pared ocupa `top..end`; `eye` es la altura del ojo.
+struct Rows { top: u32, end: u32, height: u32, half_width: f32, horizon: f32, eye: f32, dist_proj_plane: f32 }
+
+/// Proyecta cada fila por encima y por debajo de la pared de vuelta al mundo:
+/// con el ojo a altura `eye`, una fila a `p` píxeles del horizonte ve el suelo
+/// a `eye * d / p` de distancia y el techo a `(bs - eye) * d / p`.
+/// Sin textura en la casilla se usa el color liso, con la misma luz.
+fn cast_floor_and_ceiling(
+    col: &mut Column,
+    rows: &Rows,
+    a: f32,
+    block_size: usize,
+    player: &Player,
+    texman: &TextureManager,
+    scenery: &Scenery,
+) {
+    let bs = block_size as f32;
+    let hh = rows.height as f32 / 2.0;
+    let (dir_x, dir_y) = (a.cos(), a.sin());
+    let cos_off = (a - player.a).cos().max(1e-3);
+
+    let floor_at = |p: f32| rows.eye * rows.dist_proj_plane / p;
+    let ceiling_at = |p: f32| (bs - rows.eye) * rows.dist_proj_plane / p;
+    // la linterna mide el ángulo desde el centro de la pantalla
+    let dx = col.x as f32 + 0.5 - rows.half_width;
+    let torch = |c: Color, distance: f32, y: u32| {
+        scenery.torch(c, distance, dx, y as f32 + 0.5 - hh, rows.dist_proj_plane)
+    };
+    let world_at = |dist: f32| {
+        let along = dist / cos_off;
+        (player.pos.x + dir_x * along, player.pos.y + dir_y * along)
+    };
+    // clave de textura (si la casilla tiene), u y v del punto del mundo (x, y)
+    let texel = |x: f32, y: f32, pick: fn(&Surfaces) -> &[Option<char>]| {
+        let surfaces = scenery.surfaces.as_ref()?;
+        let (gx, gy) = (x / bs, y / bs);
+        let key = Surfaces::at(pick(surfaces), surfaces.width, gx.floor(), gy.floor())?;
+        Some(texman.sample_uv(key, gx.rem_euclid(1.0), gy.rem_euclid(1.0)))
+    };
+
+    for y in rows.end..rows.height {
+        let dist = floor_at(y as f32 + 0.5 - rows.horizon);
+        let (wx, wy) = world_at(dist);
+        let color = texel(wx, wy, |s| &s.floor).unwrap_or(scenery.floor);
+        let color = torch(scenery.lit(color, wx, wy), dist / cos_off, y);
+        let color = scenery.exit_pad(scenery.lighting.apply(color, dist, None), wx, wy, block_size);
+        col.set(y, scenery.crumb(color, wx, wy, block_size));
+    }
+    for y in 0..rows.top {
+        let dist = ceiling_at(rows.horizon - (y as f32 + 0.5));
+        let (wx, wy) = world_at(dist);
+        let color = match texel(wx, wy, |s| &s.ceiling) {
+            Some(c) => scenery.lighting.apply(torch(scenery.lit(c, wx, wy), dist / cos_off, y), dist, None),
+            None => torch(sky_color(scenery, texman, a, y, hh, rows.horizon), f32::INFINITY, y),
+        };
+        col.set(y, color);
+    }
+}
+
+pub fn render_minimap(
+    fb: &mut Framebuffer,
+    maze: &Maze,
+    block_size: usize,
+    player: &Player,
+    sprites: &[Sprite],
+    scenery: &Scenery,
+) {
+    let mini = 8usize;
+    let ox = 10usize;
+    let oy = 10usize;
+
+    let w = maze[0].len() * mini;
+    let h = maze.len() * mini;
+    fb.set_current_color(Color::new(20, 20, 30, 255));
+    for x in ox..ox + w { for y in oy..oy + h { fb.set_pixel(x as u32, y as u32); } }
+
+    for (j, row) in maze.iter().enumerate() {
+        for (i, &c) in row.iter().enumerate() {
+            if c == ' ' { continue; }
+            let x0 = ox + i * mini;
+            let y0 = oy + j * mini;
+            let col = match c {
+                '+'|'-'|'|' => Color::DARKPURPLE,
+                'g' if scenery.exit.is_some_and(|(_, open)| !open) => EXIT_LOCKED,
+                'g' => Color::GREEN,
+                c if is_door(c) => map_color(c),
+                _ => Color::GRAY,
+            };
+            fb.set_current_color(col);
+            for x in x0..x0 + mini { for y in y0..y0 + mini { fb.set_pixel(x as u32, y as u32); } }
+        }
+    }
+
+    // los enemigos van aparte, en su posición exacta
+    for s in sprites.iter().filter(|s| !s.collected && s.kind != ENEMY_TILE) {
+        let i = (s.x as usize) / block_size;
+        let j = (s.y as usize) / block_size;
+        let x0 = ox + i * mini;
+        let y0 = oy + j * mini;
+        fb.set_current_color(Color::GRAY);
+        for x in x0..x0 + mini { for y in y0..y0 + mini { fb.set_pixel(x as u32, y as u32); } }
+    }
+
+    for &(i, j) in scenery.breadcrumbs.as_ref().map_or(&[][..], Breadcrumbs::cells) {
+        let (cx, cy) = (ox + i * mini + mini / 2, oy + j * mini + mini / 2);
+        fb.set_current_color(CRUMB_COLOR);
+        for x in cx - 1..=cx { for y in cy - 1..=cy { fb.set_pixel(x as u32, y as u32); } }
+    }
+
+    let scale = mini as f32 / block_size as f32;
+    for s in sprites.iter().filter(|s| s.kind == ENEMY_TILE) {
+        let (ex, ey) = (ox as f32 + s.x * scale, oy as f32 + s.y * scale);
+        fb.draw_circle_filled(ex.round() as i32, ey.round() as i32, 3, Color::ORANGE);
+    }
+    let jx = ox as f32 + player.pos.x * scale;
+    let jy = oy as f32 + player.pos.y * scale;
+    fb.draw_circle_filled(jx.round() as i32, jy.round() as i32, 4, Color::RED);
+}
+
+/// Dibuja un frame completo (mundo, sprites y, si toca, minimapa) sin tocar la ventana.
+#[allow(clippy::too_many_arguments)]
+pub fn render_frame(
+    framebuffer: &mut Framebuffer,
+    maze: &Maze,
+    block_size: usize,
+    player: &Player,
+    sprites: &[Sprite],
+    depth_buffer: &mut [f32],
+    texman: &TextureManager,
+    scenery: &Scenery,
+) {
+    render_world(framebuffer, maze, block_size, player, depth_buffer, texman, scenery);
+
+    // de lejos a cerca, para que los enemigos tapen bien a los dulces
+    let mut order: Vec<&Sprite> = sprites.iter().filter(|s| !s.collected).collect();
+    let d2 = |s: &Sprite| (s.x - player.pos.x).powi(2) + (s.y - player.pos.y).powi(2);
+    order.sort_by(|a, b| d2(b).total_cmp(&d2(a)));
+    for s in order {
+        draw_sprite(framebuffer, player, s, texman, depth_buffer, block_size, scenery);
+    }
+
+    if scenery.minimap {
+        render_minimap(framebuffer, maze, block_size, player, sprites, scenery);
+    }
+}
@@ -0,0 +1,110 @@
+// scene.rs
+// Máquina de estados del juego. Cada pantalla (título, selección de nivel,
+// juego, pausa, ajustes, victoria, derrota) es una escena con `update` y
+// `draw`; el bucle de `run` solo aplica las transiciones que devuelven.
+use raylib::prelude::*;
+
+use crate::camera::Camera;
+use crate::display::ScreenTexture;
+use crate::framebuffer::Framebuffer;
+use crate::input::Input;
+use crate::levels::Manifest;
+use crate::settings::Settings;
+use crate::sound::SoundManager;
+use crate::texture::TextureManager;
+
+/// Lo que comparten las escenas y dura toda la partida.
+pub struct Game<'a> {
+    pub thread: &'a RaylibThread,
+    pub input: Input,
+    pub settings: Settings,
+    pub camera: Camera,
+    pub sound: SoundManager,
+    pub manifest: Manifest,
+    /// Semilla de `--seed` para el modo infinito.
+    pub seed: Option<u64>,
+    pub inicio_tex: Texture2D,
+    pub niveles_tex: Texture2D,
+    pub exito_tex: Texture2D,
+    pub lost_tex: Texture2D,
+    pub texman: TextureManager,
+    /// Nivel del manifiesto cuyas paredes, cielo y música están cargados.
+    pub applied_look: Option<usize>,
+    pub framebuffer: Framebuffer,
+    pub depth_buffer: Vec<f32>,
+    pub screen: ScreenTexture,
+    pub show_map: bool,
+}
+
+/// Cómo sigue el juego después del `update` de una escena.
+pub enum Transition {
+    Stay,
+    /// Pone una escena encima; la de abajo queda en pausa.
+    Push(Box<dyn Scene>),
+    /// Quita la escena de arriba y sigue la de abajo.
+    Pop,
+    /// Cambia todas las escenas por una nueva.
+    Switch(Box<dyn Scene>),
+    Quit,
+}
+
+pub trait Scene {
+    /// Avanza un frame; las entradas ya están leídas en `game.input`. Aquí se
+    /// prepara también lo que `draw` necesite subir a la ventana.
+    fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition;
+
+    fn draw(&self, d: &mut RaylibDrawHandle<'_>, game: &Game);
+
+    /// Se dibuja encima de la escena de abajo en vez de tapar la pantalla.
+    fn overlay(&self) -> bool { false }
+
+    /// Si el mouse queda capturado (para mirar) mientras está arriba.
+    fn captures_mouse(&self) -> bool { false }
+
+    /// Otra escena se puso encima; `resume` avisa cuando vuelve a estar arriba.
+    fn pause(&mut self, _now: f64) {}
+    fn resume(&mut self, _now: f64) {}
+}
+
+/// Bucle principal: lee entradas, actualiza la escena de arriba, aplica su
+/// transición y dibuja. Termina al cerrar la ventana o con `Transition::Quit`.
+pub fn run(rl: &mut RaylibHandle, game: &mut Game, first: Box<dyn Scene>) {
+    let mut stack = vec![first];
+    let mut captured = false;
+    while !rl.window_should_close() {
+        game.sound.update();
+        game.input.update(rl);
+
+        let Some(top) = stack.last_mut() else { break };
+        match top.update(rl, game) {
+            Transition::Stay => {}
+            Transition::Push(scene) => {
+                top.pause(rl.get_time());
+                stack.push(scene);
+            }
+            Transition::Pop => {
+                stack.pop();
+                if let Some(below) = stack.last_mut() { below.resume(rl.get_time()); }
+            }
+            Transition::Switch(scene) => stack = vec![scene],
+            Transition::Quit => break,
+        }
+        let Some(top) = stack.last() else { break };
+
+        if top.captures_mouse() != captured {
+            captured = !captured;
+            if captured { rl.disable_cursor(); } else { rl.enable_cursor(); }
+        }
+
+        // se dibuja la última escena que tapa la pantalla y, si es otra, la de arriba
+        let base = stack.iter().rposition(|s| !s.overlay()).unwrap_or(0);
+        {
+            let mut d = rl.begin_drawing(game.thread);
+            d.clear_background(Color::BLACK);
+            stack[base].draw(&mut d, game);
+            if base + 1 < stack.len() { top.draw(&mut d, game); }
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(16));
+    }
+}
@@ -0,0 +1,225 @@
+// screens.rs
+// Escenas fuera del juego en sí: título, selección de nivel, victoria,
+// derrota, errores de carga y los menús de pausa y ajustes.
+use raylib::prelude::*;
+
+use crate::display::draw_menu;
+use crate::endless::random_seed;
+use crate::input::Action;
+use crate::pause::{Nav, PauseChoice, PauseMenu, SettingsMenu};
+use crate::playing::Playing;
+use crate::scene::{Game, Scene, Transition};
+use crate::{Stage, SCREEN_H, SCREEN_W};
+
+/// Dibuja `tex` cubriendo toda la pantalla sin deformarla.
+fn draw_cover(d: &mut RaylibDrawHandle<'_>, tex: &Texture2D) {
+    let (screen_w, screen_h) = (SCREEN_W as f32, SCREEN_H as f32);
+    let scale = (screen_w / tex.width as f32).max(screen_h / tex.height as f32);
+    let dest_w = tex.width as f32 * scale;
+    let dest_h = tex.height as f32 * scale;
+
+    let src = Rectangle::new(0.0, 0.0, tex.width as f32, tex.height as f32);
+    let dest = Rectangle::new(
+        (screen_w - dest_w) * 0.5,
+        (screen_h - dest_h) * 0.5,
+        dest_w,
+        dest_h,
+    );
+    d.draw_texture_pro(tex, src, dest, Vector2::zero(), 0.0, Color::WHITE);
+}
+
+pub struct Title;
+
+impl Scene for Title {
+    fn update(&mut self, _rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if !game.input.pressed(Action::Confirm) { return Transition::Stay; }
+        Transition::Switch(Box::new(LevelSelect::default()))
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle<'_>, game: &Game) {
+        draw_cover(d, &game.inicio_tex);
+    }
+}
+
+/// Opciones que se pueden elegir con las teclas 1 a 9.
+const NUMBERED_OPTIONS: usize = 9;
+
+/// Lista de niveles del manifiesto; la última opción empieza una partida
+/// infinita con la semilla de `--seed` o, si no hay, con una nueva.
+#[derive(Default)]
+pub struct LevelSelect {
+    selected: usize,
+}
+
+impl Scene for LevelSelect {
+    fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        let count = game.manifest.levels.len() + 1;
+        let input = &game.input;
+        if input.pressed(Action::MoveBack) { self.selected = (self.selected + 1) % count; }
+        if input.pressed(Action::MoveForward) { self.selected = (self.selected + count - 1) % count; }
+        let chosen = match input.digit_pressed().filter(|&k| k < count) {
+            Some(k) => k,
+            None if input.pressed(Action::Confirm) => self.selected,
+            None => return Transition::Stay,
+        };
+
+        let stage = if chosen < game.manifest.levels.len() {
+            Stage::Manifest(chosen)
+        } else {
+            Stage::Endless { seed: game.seed.unwrap_or_else(random_seed), number: 1 }
+        };
+        Transition::Switch(Playing::start(stage, rl, game))
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle<'_>, game: &Game) {
+        let (screen_w, screen_h) = (SCREEN_W as i32, SCREEN_H as i32);
+        draw_cover(d, &game.niveles_tex);
+
+        // la última opción es el modo infinito
+        let names: Vec<&str> = game.manifest.levels.iter().map(|l| l.name.as_str()).chain(["Modo infinito"]).collect();
+        let font_size = 24;
+        let row_h = font_size + 12;
+        let panel_h = row_h * names.len() as i32 + 20;
+        let panel_y = screen_h - panel_h - 30;
+        d.draw_rectangle(screen_w / 6, panel_y, screen_w * 2 / 3, panel_h, Color::new(0, 0, 0, 170));
+
+        for (k, name) in names.iter().enumerate() {
+            let label = if k < NUMBERED_OPTIONS {
+                format!("{}. {}", k + 1, name)
+            } else {
+                name.to_string()
+            };
+            let color = if k == self.selected { Color::YELLOW } else { Color::WHITE };
+            let y = panel_y + 10 + row_h * k as i32 + 6;
+            d.draw_text(&label, screen_w / 6 + 20, y, font_size, color);
+        }
+    }
+}
+
+/// Nivel superado; `next` es el que sigue o `None` si era el último del manifiesto.
+pub struct Won {
+    pub next: Option<Stage>,
+}
+
+impl Scene for Won {
+    fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if !game.input.pressed(Action::Confirm) { return Transition::Stay; }
+        match self.next {
+            Some(stage) => Transition::Switch(Playing::start(stage, rl, game)),
+            None => Transition::Switch(Box::new(Title)),
+        }
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle<'_>, game: &Game) {
+        draw_cover(d, &game.exito_tex);
+    }
+}
+
+/// Se acabó el tiempo; al confirmar se repite el mismo nivel.
+pub struct Lost {
+    pub stage: Stage,
+}
+
+impl Scene for Lost {
+    fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if !game.input.pressed(Action::Confirm) { return Transition::Stay; }
+        Transition::Switch(Playing::start(self.stage, rl, game))
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle<'_>, game: &Game) {
+        draw_cover(d, &game.lost_tex);
+    }
+}
+
+/// Un archivo no se pudo cargar. Tras confirmar se vuelve a la selección de
+/// nivel o, si el error impide jugar, se sale.
+pub struct LoadError {
+    title: String,
+    detail: String,
+    fatal: bool,
+}
+
+impl LoadError {
+    pub fn new(file: &str, err: &dyn std::fmt::Display) -> LoadError {
+        LoadError { title: format!("No se pudo cargar {}", file), detail: err.to_string(), fatal: false }
+    }
+
+    pub fn fatal(file: &str, err: &dyn std::fmt::Display) -> LoadError {
+        LoadError { fatal: true, ..LoadError::new(file, err) }
+    }
+}
+
+impl Scene for LoadError {
+    fn update(&mut self, _rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if !game.input.pressed(Action::Confirm) { return Transition::Stay; }
+        if self.fatal { Transition::Quit } else { Transition::Switch(Box::new(LevelSelect::default())) }
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle<'_>, _game: &Game) {
+        let (screen_w, screen_h) = (SCREEN_W as i32, SCREEN_H as i32);
+        let hint = if self.fatal { "ENTER para salir" } else { "ENTER para volver" };
+        d.clear_background(Color::new(30, 10, 30, 255));
+
+        let title_w = d.measure_text(&self.title, 30);
+        let detail_w = d.measure_text(&self.detail, 20);
+        let hint_w = d.measure_text(hint, 20);
+        d.draw_text(&self.title, (screen_w - title_w) / 2, screen_h / 2 - 60, 30, Color::PINK);
+        d.draw_text(&self.detail, (screen_w - detail_w) / 2, screen_h / 2, 20, Color::WHITE);
+        d.draw_text(hint, (screen_w - hint_w) / 2, screen_h / 2 + 60, 20, Color::GRAY);
+    }
+}
+
+/// Menú de pausa sobre el nivel `stage`, que queda congelado debajo.
+pub struct Paused {
+    stage: Stage,
+    menu: PauseMenu,
+}
+
+impl Paused {
+    pub fn new(stage: Stage) -> Paused {
+        Paused { stage, menu: PauseMenu::default() }
+    }
+}
+
+impl Scene for Paused {
+    fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        let Some(choice) = Nav::read(&game.input).and_then(|nav| self.menu.navigate(nav)) else {
+            return Transition::Stay;
+        };
+        match choice {
+            PauseChoice::Resume => Transition::Pop,
+            PauseChoice::Restart => Transition::Switch(Playing::start(self.stage, rl, game)),
+            PauseChoice::Settings => Transition::Push(Box::new(SettingsScreen::default())),
+            PauseChoice::LevelSelect => Transition::Switch(Box::new(LevelSelect::default())),
+            PauseChoice::Quit => Transition::Quit,
+        }
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle<'_>, _game: &Game) {
+        draw_menu(d, SCREEN_W as i32, SCREEN_H as i32, &self.menu.view());
+    }
+
+    fn overlay(&self) -> bool { true }
+}
+
+/// Ajustes de cámara y mouse; cada cambio se aplica en el momento.
+#[derive(Default)]
+pub struct SettingsScreen {
+    menu: SettingsMenu,
+}
+
+impl Scene for SettingsScreen {
+    fn update(&mut self, _rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        let Some(nav) = Nav::read(&game.input) else { return Transition::Stay };
+        let close = self.menu.navigate(nav, &mut game.settings);
+        game.camera.set_settings(game.settings.camera);
+        game.input.set_invert_mouse_y(game.settings.input.invert_mouse_y);
+        if close { Transition::Pop } else { Transition::Stay }
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle<'_>, game: &Game) {
+        draw_menu(d, SCREEN_W as i32, SCREEN_H as i32, &self.menu.view(&game.settings));
+    }
+
+    fn overlay(&self) -> bool { true }
+}
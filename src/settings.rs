@@ -0,0 +1,89 @@
+// settings.rs
+// Ajustes del jugador leídos de `settings.toml`. El archivo es opcional: lo
+// que falte (o el archivo entero) toma los valores por defecto.
+use serde::Deserialize;
+
+use crate::input::InputConfig;
+
+pub const SETTINGS_FILE: &str = "settings.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub camera: CameraSettings,
+    pub input: InputConfig,
+}
+
+/// Efectos de cámara; cada uno se activa y se dosifica por separado.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+    /// Balanceo de la cabeza al caminar (y los pasos que lo acompañan).
+    pub head_bob: Effect,
+    /// Suavizado del giro con el mouse.
+    pub mouse_smoothing: Effect,
+    /// Ligero giro de la vista al moverse de lado.
+    pub strafe_sway: Effect,
+    /// Hundimiento de la vista al caer de un salto.
+    pub landing_dip: Effect,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        CameraSettings {
+            head_bob: Effect::on(1.0),
+            mouse_smoothing: Effect::on(0.5),
+            strafe_sway: Effect::on(1.0),
+            landing_dip: Effect::on(1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Effect {
+    pub enabled: bool,
+    /// 1 es la intensidad normal; 0 lo apaga igual que `enabled = false`.
+    pub strength: f32,
+}
+
+impl Default for Effect {
+    fn default() -> Self { Effect::on(1.0) }
+}
+
+impl Effect {
+    const fn on(strength: f32) -> Effect { Effect { enabled: true, strength } }
+
+    /// Intensidad efectiva: 0 si está apagado.
+    pub fn amount(&self) -> f32 {
+        if self.enabled { self.strength.clamp(0.0, 2.0) } else { 0.0 }
+    }
+}
+
+impl Settings {
+    /// Lee los ajustes; si el archivo no existe o no se entiende se usan los
+    /// de por defecto, avisando en el segundo caso.
+    pub fn load(path: &str) -> Settings {
+        let Ok(text) = std::fs::read_to_string(path) else { return Settings::default() };
+        toml::from_str(&text).unwrap_or_else(|e| {
+            eprintln!("{}: {}; se usan los ajustes por defecto", path, e);
+            Settings::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_keep_their_defaults() {
+        let s: Settings = toml::from_str(
+            "[camera.head_bob]\nenabled = false\n\n[camera.strafe_sway]\nstrength = 0.25\n",
+        ).unwrap();
+        assert_eq!(s.camera.head_bob.amount(), 0.0);
+        assert_eq!(s.camera.strafe_sway.amount(), 0.25);
+        assert_eq!(s.camera.mouse_smoothing, CameraSettings::default().mouse_smoothing);
+        assert_eq!(s.camera.landing_dip.amount(), 1.0);
+    }
+}
@@ -1,10 +1,19 @@
 use raylib::prelude::{RaylibAudio, Sound};
 use std::cell::RefCell;
 
+use crate::camera::Foot;
+
+const DEFAULT_MUSIC: &str = "sounds/candy.mp3";
+const MUSIC_VOLUME: f32 = 0.3;
+const STEP_VOLUME: f32 = 0.35;
+
 pub struct SoundManager {
     audio: &'static RaylibAudio,
     piece: Sound<'static>,
+    /// Pasos del pie izquierdo y derecho: el mismo sonido con distinto tono.
+    steps: [Sound<'static>; 2],
     music: RefCell<Sound<'static>>,
+    music_path: RefCell<String>,
 }
 
 impl SoundManager {
@@ -18,16 +27,28 @@ impl SoundManager {
             .new_sound("sounds/piece.mp3")
             .expect("No se pudo cargar sounds/piece.mp3");
 
+        // Pasos, un poco más grave el izquierdo para que no suenen iguales
+        let steps = [0.92, 1.06].map(|pitch| {
+            let mut step = audio
+                .new_sound("sounds/step.wav")
+                .expect("No se pudo cargar sounds/step.wav");
+            step.set_volume(STEP_VOLUME);
+            step.set_pitch(pitch);
+            step
+        });
+
         // Música de fondo
         let mut music = audio
-            .new_sound("sounds/candy.mp3")
+            .new_sound(DEFAULT_MUSIC)
             .expect("No se pudo cargar sounds/candy.mp3");
-        music.set_volume(0.3);
+        music.set_volume(MUSIC_VOLUME);
 
         let mgr = SoundManager {
             audio,
             piece,
+            steps,
             music: RefCell::new(music),
+            music_path: RefCell::new(DEFAULT_MUSIC.to_string()),
         };
 
         mgr.start_music();
@@ -38,6 +59,13 @@ impl SoundManager {
         self.piece.play();
     }
 
+    pub fn play_step(&self, foot: Foot) {
+        match foot {
+            Foot::Left => self.steps[0].play(),
+            Foot::Right => self.steps[1].play(),
+        }
+    }
+
     pub fn start_music(&self) {
         let mut m = self.music.borrow_mut();
         if !m.is_playing() {
@@ -52,6 +80,21 @@ impl SoundManager {
         }
     }
 
+    /// Cambia la música de fondo; no hace nada si ya está sonando esa pista.
+    pub fn set_music(&self, path: &str) {
+        if *self.music_path.borrow() == path { return; }
+        match self.audio.new_sound(path) {
+            Ok(mut music) => {
+                self.music.borrow_mut().stop();
+                music.set_volume(MUSIC_VOLUME);
+                music.play();
+                *self.music.borrow_mut() = music;
+                *self.music_path.borrow_mut() = path.to_string();
+            }
+            Err(_) => eprintln!("No se pudo cargar {}", path),
+        }
+    }
+
     pub fn set_music_volume(&self, v: f32) {
         self.music.borrow_mut().set_volume(v.clamp(0.0, 1.0));
     }
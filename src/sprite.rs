@@ -1,7 +1,7 @@
 use raylib::prelude::*;
 use std::f32::consts::PI;
 
-use crate::{framebuffer::Framebuffer, player::Player, texture::TextureManager};
+use crate::{framebuffer::Framebuffer, player::Player, render::Scenery, texture::TextureManager};
 
 pub const TRANSPARENT_COLOR: Color = Color::new(152, 0, 136, 255);
 
@@ -19,6 +19,11 @@ impl Sprite {
         let y = (j * block + block / 2) as f32;
         Self { x, y, kind, collected: false }
     }
+
+    /// Los dulces cuentan para ganar; las pilas 'B' solo recargan la linterna.
+    pub fn is_candy(&self) -> bool {
+        matches!(self.kind, 'b' | 'V')
+    }
 }
 
 #[inline]
@@ -33,9 +38,10 @@ pub fn draw_sprite(
     fb: &mut Framebuffer,
     player: &Player,
     sprite: &Sprite,
-    texman: &mut TextureManager,
+    texman: &TextureManager,
     depth_buffer: &[f32],
     block_size: usize,
+    scenery: &Scenery,
 ) {
     let dx = sprite.x - player.pos.x;
     let dy = sprite.y - player.pos.y;
@@ -59,14 +65,17 @@ pub fn draw_sprite(
     let sprite_w = sprite_h;
 
     let screen_cx = (fb.width as f32 / 2.0) + ang.tan() * proj_plane;
+    // el sprite está centrado a media pared; sube o baja según el ojo y la mirada
+    let horizon = player.horizon(fb.height, proj_plane);
+    let screen_cy = horizon + (player.eye_height(block_size) - block_size as f32 * 0.5) * proj_plane / corr;
 
     let x0 = ((screen_cx - sprite_w * 0.5).round() as i32).max(0) as usize;
     let x1 = ((screen_cx + sprite_w * 0.5).round() as i32)
         .min(fb.width as i32 - 1) as usize;
 
-    let y0 = ((fb.height as f32 / 2.0 - sprite_h * 0.5).round() as i32).max(0) as usize;
-    let y1 = ((fb.height as f32 / 2.0 + sprite_h * 0.5).round() as i32)
-        .min(fb.height as i32 - 1) as usize;
+    let y0 = ((screen_cy - sprite_h * 0.5).round() as i32).max(0) as usize;
+    let y1 = ((screen_cy + sprite_h * 0.5).round() as i32)
+        .clamp(0, fb.height as i32 - 1) as usize;
 
     if x1 <= x0 || y1 <= y0 { return; }
 
@@ -84,7 +93,7 @@ pub fn draw_sprite(
         let tx = ((1.0 - u.clamp(0.0, 1.0)) * (tw.saturating_sub(1) as f32)).round() as u32;
 
         for sy in y0..=y1 {
-            let v  = (sy as f32 - (fb.height as f32 / 2.0 - sprite_h * 0.5)) / sprite_h;
+            let v  = (sy as f32 - (screen_cy - sprite_h * 0.5)) / sprite_h;
             let ty = (v.clamp(0.0, 1.0) * (th.saturating_sub(1) as f32)).round() as u32;
 
             let c = texman.get_pixel_color(sprite.kind, tx, ty);
@@ -93,7 +102,9 @@ pub fn draw_sprite(
 
             if is_chroma(c) { continue; }
 
-            fb.set_current_color(c);
+            let c = scenery.lit(c, sprite.x, sprite.y);
+            let c = scenery.torch(c, dist, sx as f32 + 0.5 - fb.width as f32 / 2.0, sy as f32 + 0.5 - fb.height as f32 / 2.0, proj_plane);
+            fb.set_current_color(scenery.lighting.apply(c, corr, None));
             fb.set_pixel(sx as u32, sy as u32);
         }
     }
@@ -1,54 +1,113 @@
 use raylib::prelude::*;
 use std::collections::HashMap;
 
+use crate::levels::WallSet;
+
+/// Claves de las texturas de suelo y techo; no son casillas del laberinto.
+pub const FLOOR: char = '_';
+pub const FLOOR_ALT: char = '=';
+pub const CEILING: char = '^';
+pub const SKY: char = '*';
+
+const TEXTURE_FILES: &[(char, &str)] = &[
+    ('+', "assets/texture2.jpg"),
+    ('-', "assets/texture3.jpg"),
+    ('|', "assets/texture4.png"),
+    ('g', "assets/texture5.jpg"),
+    ('#', "assets/texture5.jpg"),
+    ('b', "assets/donut.png"),
+    ('V', "assets/donut.png"),
+    ('B', "assets/battery.png"),
+    ('E', "assets/gummy.png"),
+    ('r', "assets/key_red.png"),
+    ('a', "assets/key_blue.png"),
+    ('m', "assets/key_purple.png"),
+    ('R', "assets/door_red.png"),
+    ('A', "assets/door_blue.png"),
+    ('M', "assets/door_purple.png"),
+];
+
+/// Píxeles de una imagen ya decodificados, para leerlos sin `&mut` desde varios hilos.
+struct Texels {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
 pub struct TextureManager {
-    images: HashMap<char, Image>,
+    images: HashMap<char, Texels>,
     textures: HashMap<char, Texture2D>,
 }
 
 impl TextureManager {
     pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+        let mut texman = Self::new_headless();
+
+        for (ch, path) in TEXTURE_FILES {
+            if !texman.images.contains_key(ch) { continue; }
+            if let Ok(tex) = rl.load_texture(thread, path) {
+                texman.textures.insert(*ch, tex);
+            }
+        }
+
+        texman
+    }
+
+    /// Carga solo las imágenes en CPU; no necesita ventana ni contexto GL.
+    pub fn new_headless() -> Self {
         let mut images = HashMap::new();
-        let mut textures = HashMap::new();
-
-        let texture_files = vec![
-            ('+', "assets/texture2.jpg"),
-            ('-', "assets/texture3.jpg"),
-            ('|', "assets/texture4.png"),
-            ('g', "assets/texture5.jpg"),
-            ('#', "assets/texture5.jpg"),
-            ('b', "assets/donut.png"), 
-            ('V', "assets/donut.png"),
-        ];
-
-        for (ch, path) in texture_files {
-            if let Ok(image) = Image::load_image(path) {
-                images.insert(ch, image);
-                if let Ok(tex) = rl.load_texture(thread, path) {
-                    textures.insert(ch, tex);
-                }
-            } else {
-                eprintln!("Failed to load image {}", path);
+
+        for (ch, path) in TEXTURE_FILES {
+            if let Some(texels) = load_texels(path) {
+                images.insert(*ch, texels);
+            }
+        }
+
+        TextureManager { images, textures: HashMap::new() }
+    }
+
+    /// Cambia las imágenes de las paredes por las del conjunto del nivel.
+    /// Si alguna no carga se conserva la anterior.
+    pub fn use_wall_set(&mut self, set: &WallSet) {
+        for (ch, path) in set.entries() {
+            if let Some(texels) = load_texels(path) {
+                self.images.insert(ch, texels);
             }
         }
+        for (ch, path) in set.surfaces() {
+            match path.and_then(load_texels) {
+                Some(texels) => { self.images.insert(ch, texels); }
+                None => { self.images.remove(&ch); }
+            }
+        }
+    }
+
+    /// Carga el panorama del cielo, o lo quita si el nivel no tiene.
+    pub fn use_sky(&mut self, path: Option<&str>) {
+        match path.and_then(load_texels) {
+            Some(texels) => { self.images.insert(SKY, texels); }
+            None => { self.images.remove(&SKY); }
+        }
+    }
 
-        TextureManager { images, textures }
+    pub fn has(&self, ch: char) -> bool {
+        self.images.contains_key(&ch)
     }
 
-    pub fn get_pixel_color(&mut self, ch: char, tx: u32, ty: u32) -> Color {
-        if let Some(image) = self.images.get_mut(&ch) {
-            let x = tx.min(image.width.max(1) as u32 - 1) as i32;
-            let y = ty.min(image.height.max(1) as u32 - 1) as i32;
-            image.get_color(x, y)
+    pub fn get_pixel_color(&self, ch: char, tx: u32, ty: u32) -> Color {
+        if let Some(image) = self.images.get(&ch) {
+            let x = tx.min(image.width - 1);
+            let y = ty.min(image.height - 1);
+            image.pixels.get((y * image.width + x) as usize).copied().unwrap_or(Color::WHITE)
         } else {
             Color::WHITE
         }
     }
 
-    pub fn sample_uv(&mut self, ch: char, u: f32, v: f32) -> Color {
-        if let Some(image) = self.images.get_mut(&ch) {
-            let w = image.width.max(1) as f32;
-            let h = image.height.max(1) as f32;
+    pub fn sample_uv(&self, ch: char, u: f32, v: f32) -> Color {
+        if let Some(image) = self.images.get(&ch) {
+            let w = image.width as f32;
+            let h = image.height as f32;
             let uu = u.fract().abs().clamp(0.0, 1.0);
             let vv = v.fract().abs().clamp(0.0, 1.0);
             let tx = (uu * (w - 1.0)).round() as u32;
@@ -67,9 +126,23 @@ impl TextureManager {
 impl TextureManager {
     pub fn image_size(&self, ch: char) -> (u32, u32) {
         if let Some(img) = self.images.get(&ch) {
-            (img.width.max(1) as u32, img.height.max(1) as u32)
+            (img.width, img.height)
         } else {
             (128, 128)
         }
     }
-}
\ No newline at end of file
+}
+
+fn load_texels(path: &str) -> Option<Texels> {
+    match Image::load_image(path) {
+        Ok(image) => Some(Texels {
+            width: image.width.max(1) as u32,
+            height: image.height.max(1) as u32,
+            pixels: image.get_image_data().to_vec(),
+        }),
+        Err(_) => {
+            eprintln!("Failed to load image {}", path);
+            None
+        }
+    }
+}